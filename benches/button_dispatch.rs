@@ -0,0 +1,74 @@
+//! Heap allocations and time per dispatched button event.
+//!
+//! Run with `cargo bench --bench button_dispatch`. Allocations are counted
+//! with a global allocator, for a driver with a listener on
+//! `button_events` and for one with a listener on `events`.
+
+extern crate carboxyl;
+extern crate carboxyl_window;
+extern crate glutin;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+use carboxyl::Signal;
+use glutin::ElementState::{Pressed, Released};
+use glutin::Event::KeyboardInput;
+use glutin::VirtualKeyCode::A;
+use carboxyl_window::WindowDriver;
+use carboxyl_window::testing::MockWindow;
+
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+const BUTTONS: usize = 100_000;
+
+/// Dispatch `BUTTONS` alternating presses and releases in one poll and
+/// report allocations and nanoseconds per event.
+fn measure<F>(name: &str, listen: F)
+    where F: FnOnce(&WindowDriver<::std::rc::Rc<::std::cell::RefCell<MockWindow>>>)
+              -> Signal<usize>
+{
+    let window = MockWindow::shared();
+    let mut driver = WindowDriver::new(window.clone());
+    let count = listen(&driver);
+    // Warm up, and leave the empty poll of a step out of the counts
+    driver.step(0);
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    driver.step(1);
+    let empty = ALLOCATIONS.load(Ordering::Relaxed) - before;
+    for i in 0..BUTTONS {
+        let state = if i % 2 == 0 { Pressed } else { Released };
+        window.borrow_mut().push(KeyboardInput(state, 0, Some(A)));
+    }
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    let start = Instant::now();
+    driver.step(2);
+    let elapsed = start.elapsed();
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - before - empty;
+    assert_eq!(count.sample(), BUTTONS);
+    println!("{:<16} {:>8.2} allocations/event {:>10.1} ns/event",
+             name,
+             allocations as f64 / BUTTONS as f64,
+             elapsed.as_nanos() as f64 / BUTTONS as f64);
+}
+
+fn main() {
+    measure("button_events", |driver| driver.button_events().fold(0, |n, _| n + 1));
+    measure("events", |driver| driver.events().fold(0, |n, _| n + 1));
+}
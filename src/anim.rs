@@ -0,0 +1,207 @@
+//! Animated signals advanced by the run loop.
+//!
+//! Animations take the driver's tick stream (see `WindowDriver::ticks`) as
+//! their clock and update once per tick.
+
+use std::time::Duration;
+use carboxyl::{Signal, Stream};
+use idle::nanoseconds;
+
+/// Values that can be interpolated linearly.
+pub trait Interpolate: Clone + PartialEq + Send + Sync + 'static {
+    /// The value a fraction `t` of the way from `self` to `other`.
+    fn lerp(&self, other: &Self, t: f64) -> Self;
+}
+
+impl Interpolate for f64 {
+    fn lerp(&self, other: &f64, t: f64) -> f64 {
+        self + (other - self) * t
+    }
+}
+
+impl Interpolate for (f64, f64) {
+    fn lerp(&self, other: &(f64, f64), t: f64) -> (f64, f64) {
+        (self.0.lerp(&other.0, t), self.1.lerp(&other.1, t))
+    }
+}
+
+impl Interpolate for (f64, f64, f64) {
+    fn lerp(&self, other: &(f64, f64, f64), t: f64) -> (f64, f64, f64) {
+        (self.0.lerp(&other.0, t), self.1.lerp(&other.1, t), self.2.lerp(&other.2, t))
+    }
+}
+
+/// State of a tween towards a target value.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Tween<T> {
+    pub from: T,
+    pub to: T,
+    /// When the current transition started, in nanoseconds.
+    pub start: Option<u64>,
+    pub value: T
+}
+
+impl<T: Interpolate> Tween<T> {
+    /// A tween resting at `value`.
+    pub fn new(value: T) -> Tween<T> {
+        Tween { from: value.clone(), to: value.clone(), start: None, value: value }
+    }
+
+    /// Advance to `now` with the latest `target`.
+    ///
+    /// A changed target starts a new transition from the current value, so
+    /// retargeting mid-animation does not jump.
+    pub fn advance(self, target: T, now: u64, duration: u64, easing: fn(f64) -> f64)
+        -> Tween<T>
+    {
+        let current = if target != self.to {
+            Tween { from: self.value.clone(), to: target, start: Some(now), .. self }
+        } else {
+            self
+        };
+        let start = current.start.unwrap_or(now);
+        let t = if duration == 0 {
+            1.0
+        } else {
+            (now.saturating_sub(start) as f64 / duration as f64).min(1.0)
+        };
+        Tween {
+            value: current.from.lerp(&current.to, easing(t)),
+            start: Some(start),
+            .. current
+        }
+    }
+}
+
+/// Follow `target`, moving to each new value over `duration` along the
+/// curve `easing`, which maps normalized time in `[0, 1]` to progress (see
+/// the `easing` module).
+pub fn tween<T: Interpolate>(target: &Signal<T>, ticks: &Stream<u64>,
+                             duration: Duration, easing: fn(f64) -> f64)
+    -> Signal<T>
+{
+    let duration = nanoseconds(duration);
+    target.snapshot(ticks, |target, now| (target, now))
+        .fold(Tween::new(target.sample()), move |tween, (target, now)|
+            tween.advance(target, now, duration, easing))
+        .map(|tween| tween.value)
+}
+
+/// Longest integration step of a spring, in seconds.
+const SPRING_STEP: f64 = 1.0 / 240.0;
+
+/// State of a damped spring pulled towards a target.
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub struct Spring {
+    pub position: f64,
+    pub velocity: f64,
+    pub time: Option<u64>
+}
+
+impl Spring {
+    pub fn new(position: f64) -> Spring {
+        Spring { position: position, velocity: 0.0, time: None }
+    }
+
+    /// Integrate up to `now` while pulled towards `target`.
+    ///
+    /// Long frames are split into short steps so that stiff springs stay
+    /// stable.
+    pub fn advance(self, target: f64, now: u64, stiffness: f64, damping: f64)
+        -> Spring
+    {
+        let mut dt = match self.time {
+            Some(last) => now.saturating_sub(last) as f64 * 1e-9,
+            None => 0.0
+        };
+        let (mut position, mut velocity) = (self.position, self.velocity);
+        while dt > 0.0 {
+            let step = dt.min(SPRING_STEP);
+            let force = stiffness * (target - position) - damping * velocity;
+            velocity += force * step;
+            position += velocity * step;
+            dt -= step;
+        }
+        Spring { position: position, velocity: velocity, time: Some(now) }
+    }
+}
+
+/// Damping that settles a spring of `stiffness` as fast as possible without
+/// overshooting.
+pub fn critical_damping(stiffness: f64) -> f64 {
+    2.0 * stiffness.sqrt()
+}
+
+/// Follow `target` like a mass on a damped spring, advanced on every tick.
+///
+/// Use `critical_damping` for motion that approaches the target without
+/// oscillating; less damping makes it bounce.
+pub fn spring(target: &Signal<f64>, ticks: &Stream<u64>, stiffness: f64, damping: f64)
+    -> Signal<f64>
+{
+    assert!(stiffness > 0.0 && damping >= 0.0);
+    target.snapshot(ticks, |target, now| (target, now))
+        .fold(Spring::new(target.sample()), move |spring, (target, now)|
+            spring.advance(target, now, stiffness, damping))
+        .map(|spring| spring.position)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use carboxyl::Sink;
+    use super::{tween, Tween, Spring, critical_damping};
+
+    fn linear(t: f64) -> f64 { t }
+
+    #[test]
+    fn interpolates_towards_the_target() {
+        let tween = Tween::new(0.0)
+            .advance(10.0, 100, 100, linear)
+            .advance(10.0, 150, 100, linear);
+        assert_eq!(tween.value, 5.0);
+        assert_eq!(tween.advance(10.0, 300, 100, linear).value, 10.0);
+    }
+
+    #[test]
+    fn retargets_from_the_current_value() {
+        let tween = Tween::new(0.0)
+            .advance(10.0, 0, 100, linear)
+            .advance(10.0, 50, 100, linear)
+            .advance(0.0, 50, 100, linear)
+            .advance(0.0, 100, 100, linear);
+        assert_eq!(tween.value, 2.5);
+    }
+
+    #[test]
+    fn tweens_a_signal_on_ticks() {
+        let targets = Sink::new();
+        let ticks = Sink::new();
+        let value = tween(&targets.stream().hold((0.0, 0.0)), &ticks.stream(),
+                          Duration::new(0, 100), linear);
+        targets.send((4.0, 8.0));
+        ticks.send(0);
+        ticks.send(25);
+        assert_eq!(value.sample(), (1.0, 2.0));
+    }
+
+    #[test]
+    fn critically_damped_spring_settles_without_overshoot() {
+        let damping = critical_damping(100.0);
+        let mut spring = Spring::new(0.0).advance(1.0, 0, 100.0, damping);
+        let mut max: f64 = 0.0;
+        for frame in 1..120 {
+            spring = spring.advance(1.0, frame * 16_666_667, 100.0, damping);
+            max = max.max(spring.position);
+        }
+        assert!(max <= 1.0);
+        assert!((spring.position - 1.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn spring_does_not_move_without_elapsed_time() {
+        let spring = Spring::new(0.0).advance(5.0, 10, 50.0, 1.0);
+        assert_eq!(spring.position, 0.0);
+    }
+}
@@ -0,0 +1,116 @@
+use quickcheck::{Arbitrary, Gen};
+use glutin::MouseButton;
+use fuzz::KEYS;
+use ::{Button, ButtonEvent, ButtonState, Event, ScrollDelta, Timed};
+
+impl Arbitrary for Button {
+    fn arbitrary(g: &mut Gen) -> Button {
+        if bool::arbitrary(g) {
+            Button::Keyboard(*g.choose(&KEYS).unwrap())
+        } else {
+            let other = MouseButton::Other(u8::arbitrary(g));
+            Button::Mouse(*g.choose(&[
+                MouseButton::Left,
+                MouseButton::Right,
+                MouseButton::Middle,
+                other
+            ]).unwrap())
+        }
+    }
+}
+
+impl Arbitrary for ButtonState {
+    fn arbitrary(g: &mut Gen) -> ButtonState {
+        if bool::arbitrary(g) { ButtonState::Pressed } else { ButtonState::Released }
+    }
+}
+
+impl Arbitrary for ButtonEvent {
+    fn arbitrary(g: &mut Gen) -> ButtonEvent {
+        ButtonEvent::new(Button::arbitrary(g), ButtonState::arbitrary(g))
+    }
+}
+
+impl Arbitrary for Event {
+    fn arbitrary(g: &mut Gen) -> Event {
+        match u8::arbitrary(g) % 8 {
+            0 => Event::Press(Button::arbitrary(g)),
+            1 => Event::Release(Button::arbitrary(g)),
+            2 => match Button::arbitrary(g) {
+                Button::Mouse(button) =>
+                    Event::Click(button, 1 + u32::arbitrary(g) % 3),
+                Button::Keyboard(_) => Event::Click(MouseButton::Left, 1)
+            },
+            3 => Event::Text(String::arbitrary(g)),
+            4 => Event::Suspend,
+            5 => Event::Resume,
+            6 => Event::Repeat(Button::arbitrary(g)),
+            _ => Event::Shutdown
+        }
+    }
+
+    fn shrink(&self) -> Box<dyn Iterator<Item = Event>> {
+        match *self {
+            Event::Text(ref text) =>
+                Box::new(text.shrink().map(Event::Text)),
+            _ => Box::new(None.into_iter())
+        }
+    }
+}
+
+impl Arbitrary for ScrollDelta {
+    fn arbitrary(g: &mut Gen) -> ScrollDelta {
+        let x = (i16::arbitrary(g) as f64) / 16.0;
+        let y = (i16::arbitrary(g) as f64) / 16.0;
+        if bool::arbitrary(g) {
+            ScrollDelta::Lines(x, y)
+        } else {
+            ScrollDelta::Pixels(x, y)
+        }
+    }
+}
+
+impl<T: Arbitrary> Arbitrary for Timed<T> {
+    fn arbitrary(g: &mut Gen) -> Timed<T> {
+        Timed::new(u64::arbitrary(g), u64::arbitrary(g), T::arbitrary(g))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use quickcheck::quickcheck;
+    use glutin::{self, ElementState};
+    use testing::simulate;
+    use ::{Button, ButtonEvent, ButtonState, Event};
+
+    fn backend_event(event: &ButtonEvent) -> glutin::Event {
+        let state = match event.state {
+            ButtonState::Pressed => ElementState::Pressed,
+            ButtonState::Released => ElementState::Released
+        };
+        match event.button {
+            Button::Keyboard(key) => glutin::Event::KeyboardInput(state, 0, Some(key)),
+            Button::Mouse(button) => glutin::Event::MouseInput(state, button)
+        }
+    }
+
+    #[test]
+    fn button_events_round_trip_through_dispatch() {
+        fn property(buttons: Vec<ButtonEvent>) -> bool {
+            let batch = buttons.iter().map(backend_event).collect();
+            let dispatched: Vec<ButtonEvent> = simulate(vec![(0, batch)]).events
+                .into_iter()
+                .filter_map(|event| match event.value {
+                    Event::Press(button) =>
+                        Some(ButtonEvent::new(button, ButtonState::Pressed)),
+                    Event::Release(button) =>
+                        Some(ButtonEvent::new(button, ButtonState::Released)),
+                    _ => None
+                })
+                .collect();
+            dispatched == buttons
+        }
+        quickcheck(property as fn(Vec<ButtonEvent>) -> bool);
+    }
+}
@@ -0,0 +1,133 @@
+//! Event sources for the supported windowing backends.
+//!
+//! A backend only decides how the event source is constructed. The driver
+//! and everything derived from it work the same with any of them. Only
+//! glutin windows and a headless source exist; the driver is built on
+//! glutin's event types, so other windowing libraries cannot be plugged in.
+
+use std::cell::Cell;
+use glutin;
+use source::EventSource;
+
+/// An event source with the backend picked at runtime.
+pub enum Backend {
+    Glutin(glutin::Window),
+    Headless(Headless)
+}
+
+impl Backend {
+    /// Open a glutin window if the system has a display to open it on, and
+    /// fall back to a headless source of size `(0, 0)` otherwise.
+    ///
+    /// Only a missing display leads to the fallback. Any other error, such as
+    /// an unsupported context or pixel format, is returned. On Linux glutin
+    /// already chooses between Wayland and X11 by itself.
+    pub fn auto(builder: glutin::WindowBuilder)
+        -> Result<Backend, glutin::CreationError>
+    {
+        match builder.build() {
+            Ok(window) => Ok(Backend::Glutin(window)),
+            Err(glutin::CreationError::NoBackendAvailable(_)) =>
+                Ok(Backend::Headless(Headless::new(0, 0))),
+            Err(error) => Err(error)
+        }
+    }
+
+    pub fn is_headless(&self) -> bool {
+        matches!(*self, Backend::Headless(_))
+    }
+
+    fn source(&self) -> &dyn EventSource {
+        match *self {
+            Backend::Glutin(ref window) => window,
+            Backend::Headless(ref headless) => headless
+        }
+    }
+}
+
+impl EventSource for Backend {
+    fn poll_events(&mut self) -> Vec<glutin::Event> {
+        match *self {
+            Backend::Glutin(ref mut window) => EventSource::poll_events(window),
+            Backend::Headless(ref mut headless) => headless.poll_events()
+        }
+    }
+
+    fn inner_size(&self) -> Option<(u32, u32)> {
+        self.source().inner_size()
+    }
+
+    fn position(&self) -> Option<(i32, i32)> {
+        self.source().position()
+    }
+
+    fn set_inner_size(&self, width: u32, height: u32) {
+        self.source().set_inner_size(width, height)
+    }
+
+    fn set_position(&self, x: i32, y: i32) {
+        self.source().set_position(x, y)
+    }
+}
+
+/// An event source without a window, for servers and batch jobs that run a
+/// signal network on its clock alone.
+///
+/// It never reports events and keeps the size it is given.
+#[derive(Debug, Default)]
+pub struct Headless {
+    size: Cell<(u32, u32)>,
+    position: Cell<(i32, i32)>
+}
+
+impl Headless {
+    pub fn new(width: u32, height: u32) -> Headless {
+        Headless { size: Cell::new((width, height)), position: Cell::new((0, 0)) }
+    }
+}
+
+impl EventSource for Headless {
+    fn poll_events(&mut self) -> Vec<glutin::Event> {
+        Vec::new()
+    }
+
+    fn inner_size(&self) -> Option<(u32, u32)> {
+        Some(self.size.get())
+    }
+
+    fn position(&self) -> Option<(i32, i32)> {
+        Some(self.position.get())
+    }
+
+    fn set_inner_size(&self, width: u32, height: u32) {
+        self.size.set((width, height));
+    }
+
+    fn set_position(&self, x: i32, y: i32) {
+        self.position.set((x, y));
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use source::EventSource;
+    use super::{Backend, Headless};
+
+    #[test]
+    fn headless_source_keeps_its_size() {
+        let mut headless = Headless::new(320, 240);
+        assert!(headless.poll_events().is_empty());
+        headless.set_inner_size(640, 480);
+        assert_eq!(headless.inner_size(), Some((640, 480)));
+    }
+
+    #[test]
+    fn backend_delegates_to_the_chosen_source() {
+        let backend = Backend::Headless(Headless::new(4, 3));
+        assert!(backend.is_headless());
+        backend.set_position(5, 6);
+        assert_eq!(backend.inner_size(), Some((4, 3)));
+        assert_eq!(backend.position(), Some((5, 6)));
+    }
+}
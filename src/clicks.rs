@@ -0,0 +1,98 @@
+use glutin::MouseButton;
+
+/// Default maximum time between presses of a multi-click in nanoseconds.
+pub const DEFAULT_INTERVAL: u64 = 500_000_000;
+
+/// Default maximum cursor travel between presses of a multi-click in pixels.
+pub const DEFAULT_DISTANCE: f64 = 4.0;
+
+#[derive(Clone, Copy)]
+struct LastPress {
+    button: MouseButton,
+    time: u64,
+    position: (f64, f64),
+    count: u32
+}
+
+/// Groups successive presses of the same mouse button into multi-clicks.
+pub struct ClickCounter {
+    interval: u64,
+    distance: f64,
+    last: Option<LastPress>
+}
+
+impl ClickCounter {
+    pub fn new(interval: u64, distance: f64) -> ClickCounter {
+        ClickCounter { interval: interval, distance: distance, last: None }
+    }
+
+    pub fn set_interval(&mut self, interval: u64) {
+        self.interval = interval;
+    }
+
+    /// Register a press and return its click count (1 for a single click).
+    pub fn press(&mut self, button: MouseButton, time: u64, position: (f64, f64))
+        -> u32
+    {
+        let count = match self.last {
+            Some(last) if last.button == button
+                && time.saturating_sub(last.time) <= self.interval
+                && distance(last.position, position) <= self.distance
+                => last.count + 1,
+            _ => 1
+        };
+        self.last = Some(LastPress {
+            button: button,
+            time: time,
+            position: position,
+            count: count
+        });
+        count
+    }
+
+    pub fn reset(&mut self) {
+        self.last = None;
+    }
+}
+
+impl Default for ClickCounter {
+    fn default() -> ClickCounter {
+        ClickCounter::new(DEFAULT_INTERVAL, DEFAULT_DISTANCE)
+    }
+}
+
+fn distance(a: (f64, f64), b: (f64, f64)) -> f64 {
+    ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use glutin::MouseButton::{Left, Right};
+    use super::ClickCounter;
+
+    const MS: u64 = 1_000_000;
+
+    #[test]
+    fn counts_rapid_presses_up_to_triple_click() {
+        let mut counter = ClickCounter::default();
+        assert_eq!(counter.press(Left, 0, (10.0, 10.0)), 1);
+        assert_eq!(counter.press(Left, 200 * MS, (10.0, 11.0)), 2);
+        assert_eq!(counter.press(Left, 400 * MS, (11.0, 11.0)), 3);
+    }
+
+    #[test]
+    fn starts_over_after_the_interval() {
+        let mut counter = ClickCounter::default();
+        counter.press(Left, 0, (0.0, 0.0));
+        assert_eq!(counter.press(Left, 900 * MS, (0.0, 0.0)), 1);
+    }
+
+    #[test]
+    fn starts_over_for_another_button_or_position() {
+        let mut counter = ClickCounter::default();
+        counter.press(Left, 0, (0.0, 0.0));
+        assert_eq!(counter.press(Right, 100 * MS, (0.0, 0.0)), 1);
+        assert_eq!(counter.press(Right, 200 * MS, (30.0, 0.0)), 1);
+    }
+}
@@ -0,0 +1,34 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// A thread-safe handle to request that the run loop ends.
+///
+/// The request is picked up on the next tick and leads to the same orderly
+/// shutdown as closing the window, which makes it suitable for calling from
+/// signal handlers.
+#[derive(Clone, Debug, Default)]
+pub struct CloseHandle {
+    requested: Arc<AtomicBool>
+}
+
+impl CloseHandle {
+    pub fn new() -> CloseHandle {
+        CloseHandle::default()
+    }
+
+    pub fn request_close(&self) {
+        self.requested.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_requested(&self) -> bool {
+        self.requested.load(Ordering::SeqCst)
+    }
+
+    /// Request a close when the process receives Ctrl+C or (on Unix) a
+    /// terminate signal. Only one handler can be installed per process.
+    #[cfg(feature = "ctrlc")]
+    pub fn close_on_signal(&self) -> Result<(), ::ctrlc::Error> {
+        let handle = self.clone();
+        ::ctrlc::set_handler(move || handle.request_close())
+    }
+}
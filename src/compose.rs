@@ -0,0 +1,166 @@
+use glutin;
+
+/// Accents that dead keys produce, with the letters they combine with and
+/// the results, position by position.
+const ACCENTS: [(char, &str, &str); 5] = [
+    ('\u{b4}', "aeiouyAEIOUY", "áéíóúýÁÉÍÓÚÝ"),
+    ('`', "aeiouAEIOU", "àèìòùÀÈÌÒÙ"),
+    ('^', "aeiouAEIOU", "âêîôûÂÊÎÔÛ"),
+    ('\u{a8}', "aeiouyAEIOU", "äëïöüÿÄËÏÖÜ"),
+    ('~', "anoANO", "ãñõÃÑÕ")
+];
+
+fn compose(accent: char, base: char) -> Option<char> {
+    let &(_, bases, composed) = ACCENTS.iter().find(|&&(a, _, _)| a == accent)?;
+    let index = bases.chars().position(|b| b == base)?;
+    composed.chars().nth(index)
+}
+
+fn is_dead(c: char) -> bool {
+    ACCENTS.iter().any(|&(accent, _, _)| accent == c)
+}
+
+/// Composes accents from dead keys with the following letter, for backends
+/// that deliver a dead key as a character of its own.
+///
+/// Such a backend sends the dead key's press without a character, and the
+/// accent only along with the character of the next key, without a press in
+/// between. Only an accent received that way is composed, so typing an
+/// accent character on its own passes it on unchanged.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Composer {
+    pending: Option<char>,
+    /// Whether the last key press has not produced a character yet.
+    silent_press: bool,
+    /// Whether a key press went without a character, marking a dead key.
+    dead: bool
+}
+
+impl Composer {
+    /// Feed a received character and return the ones to pass on: none while
+    /// waiting for the letter after a dead key, the composed letter, or the
+    /// accent followed by the character if they do not compose.
+    ///
+    /// A space after a dead key yields the accent alone.
+    fn feed(&mut self, c: char) -> Vec<char> {
+        let dead = self.dead;
+        self.dead = false;
+        self.silent_press = false;
+        match self.pending.take() {
+            None if dead && is_dead(c) => {
+                self.pending = Some(c);
+                vec![]
+            },
+            None => vec![c],
+            Some(accent) if c == ' ' => vec![accent],
+            Some(accent) => match compose(accent, c) {
+                Some(composed) => vec![composed],
+                None => vec![accent, c]
+            }
+        }
+    }
+
+    /// Forget a pending dead key, e.g. when the window loses focus.
+    pub fn reset(&mut self) {
+        *self = Composer::default();
+    }
+
+    /// Compose the received characters in a batch of backend events.
+    ///
+    /// An accent that is still pending at the end of the batch, or when
+    /// anything else than text arrives, is passed on as it is.
+    pub fn compose_events(&mut self, events: Vec<glutin::Event>) -> Vec<glutin::Event> {
+        use glutin::ElementState::Pressed;
+        let mut composed = Vec::with_capacity(events.len());
+        for event in events {
+            if let glutin::Event::ReceivedCharacter(c) = event {
+                composed.extend(
+                    self.feed(c).into_iter().map(glutin::Event::ReceivedCharacter));
+                continue;
+            }
+            composed.extend(self.pending.take().map(glutin::Event::ReceivedCharacter));
+            match event {
+                glutin::Event::KeyboardInput(Pressed, _, key) if !is_modifier(key) => {
+                    self.dead = self.silent_press;
+                    self.silent_press = true;
+                },
+                glutin::Event::Focused(false) => self.reset(),
+                _ => ()
+            }
+            composed.push(event);
+        }
+        composed.extend(self.pending.take().map(glutin::Event::ReceivedCharacter));
+        composed
+    }
+}
+
+/// Whether `key` is a modifier, which never produces a character of its own.
+fn is_modifier(key: Option<glutin::VirtualKeyCode>) -> bool {
+    use glutin::VirtualKeyCode::*;
+    matches!(key,
+        Some(LShift) | Some(RShift) | Some(LControl) | Some(RControl) |
+        Some(LAlt) | Some(RAlt) | Some(LWin) | Some(RWin))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use glutin::ElementState::Pressed;
+    use glutin::Event::{KeyboardInput, ReceivedCharacter};
+    use glutin::VirtualKeyCode::{self, A, E, N, Grave, LShift};
+    use glutin;
+    use super::Composer;
+
+    fn text(events: Vec<glutin::Event>) -> String {
+        Composer::default().compose_events(events).into_iter()
+            .filter_map(|event| match event {
+                ReceivedCharacter(c) => Some(c),
+                _ => None
+            })
+            .collect()
+    }
+
+    fn press(key: VirtualKeyCode) -> glutin::Event {
+        KeyboardInput(Pressed, 0, Some(key))
+    }
+
+    /// Events of typing `c` with a key that produces it directly.
+    fn typed(key: VirtualKeyCode, c: char) -> Vec<glutin::Event> {
+        vec![press(key), ReceivedCharacter(c)]
+    }
+
+    /// Events of a dead key followed by another key, which then delivers the
+    /// accent and its own character.
+    fn dead(accent: char, key: VirtualKeyCode, c: char) -> Vec<glutin::Event> {
+        vec![press(Grave), press(key), ReceivedCharacter(accent), ReceivedCharacter(c)]
+    }
+
+    #[test]
+    fn composes_accents_of_dead_keys_with_letters() {
+        assert_eq!(text(dead('\u{b4}', E, 'e')), "é");
+        let mut events = vec![press(LShift)];
+        events.extend(dead('~', N, 'n'));
+        events.extend(dead('^', A, 'A'));
+        assert_eq!(text(events), "ñÂ");
+    }
+
+    #[test]
+    fn passes_on_typed_accents() {
+        let mut events = typed(Grave, '`');
+        events.extend(typed(A, 'a'));
+        events.push(press(LShift));
+        events.extend(typed(Grave, '~'));
+        events.extend(typed(N, 'n'));
+        assert_eq!(text(events), "`a~n");
+    }
+
+    #[test]
+    fn flushes_accents_that_do_not_compose() {
+        assert_eq!(text(dead('`', A, 'x')), "`x");
+        assert_eq!(text(dead('^', A, ' ')), "^");
+        let mut events = vec![press(Grave), press(A), ReceivedCharacter('~')];
+        assert_eq!(text(events.clone()), "~");
+        events.extend(typed(N, 'n'));
+        assert_eq!(text(events), "~n");
+    }
+}
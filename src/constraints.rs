@@ -0,0 +1,29 @@
+/// Clamp a window size to the given limits component-wise. The maximum wins
+/// where the limits contradict each other.
+pub fn clamp_size(size: (u32, u32), min: (u32, u32), max: (u32, u32)) -> (u32, u32) {
+    (
+        size.0.max(min.0).min(max.0),
+        size.1.max(min.1).min(max.1)
+    )
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::clamp_size;
+
+    #[test]
+    fn keeps_sizes_within_limits() {
+        assert_eq!(clamp_size((640, 480), (100, 100), (800, 600)), (640, 480));
+    }
+
+    #[test]
+    fn clamps_each_dimension_separately() {
+        assert_eq!(clamp_size((50, 900), (100, 100), (800, 600)), (100, 600));
+    }
+
+    #[test]
+    fn prefers_the_maximum_when_limits_overlap() {
+        assert_eq!(clamp_size((300, 300), (500, 500), (400, 400)), (400, 400));
+    }
+}
@@ -1,3 +1,9 @@
+//! Plain event data types, without carboxyl signals or streams. They are not
+//! backend-independent: keys, mouse buttons and touch phases are glutin's
+//! enums, so using them still pulls in glutin.
+
+use glutin::{MouseButton, TouchPhase, VirtualKeyCode};
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct WindowProperties {
     pub position: (i32, i32),
@@ -39,21 +45,238 @@ impl Cursor {
     }
 }
 
+/// State of the keyboard's lock keys.
+///
+/// The backend offers no way to query these at startup, so both are assumed
+/// to be off until the first toggle is observed. Toggles made while the
+/// window is unfocused are never reported to it and are missed as well.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct LockKeys {
+    pub caps: bool,
+    pub num: bool
+}
+
+impl LockKeys {
+    pub fn new(caps: bool, num: bool) -> LockKeys {
+        LockKeys { caps: caps, num: num }
+    }
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Default)]
 pub struct Context {
     pub window: WindowProperties,
-    pub cursor: Cursor
+    pub cursor: Cursor,
+    pub lock_keys: LockKeys
 }
 
 impl Context {
-    pub fn new(window: WindowProperties, cursor: Cursor) -> Context {
-        Context { window: window, cursor: cursor }
+    pub fn new(window: WindowProperties, cursor: Cursor, lock_keys: LockKeys)
+        -> Context
+    {
+        Context { window: window, cursor: cursor, lock_keys: lock_keys }
+    }
+}
+
+/// A value together with the time it was dispatched at, in nanoseconds, and
+/// its sequence number.
+///
+/// glutin does not report when the system received an event, so the time is
+/// the moment the run loop picked it up. Every backend event is assigned the
+/// next sequence number, which is shared by everything derived from it (a
+/// press and its click, for instance). Across all streams, values are sent in
+/// order of their sequence numbers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Timed<T> {
+    pub time: u64,
+    pub seq: u64,
+    pub value: T
+}
+
+impl<T> Timed<T> {
+    pub fn new(time: u64, seq: u64, value: T) -> Timed<T> {
+        Timed { time: time, seq: seq, value: value }
+    }
+}
+
+/// A wheel or touchpad scroll, tagged with its unit.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ScrollDelta {
+    /// Line-based steps, as reported by notched mouse wheels.
+    Lines(f64, f64),
+    /// Pixel-precise deltas, as reported by touchpads.
+    Pixels(f64, f64)
+}
+
+impl ScrollDelta {
+    pub fn to_pixels(self, line_height: f64) -> (f64, f64) {
+        match self {
+            ScrollDelta::Lines(x, y) => (x * line_height, y * line_height),
+            ScrollDelta::Pixels(x, y) => (x, y)
+        }
+    }
+}
+
+/// A single touch point.
+///
+/// Pressure (normalized to `0.0..1.0`) and contact radius (in pixels) are
+/// only present where the backend reports them. glutin 0.7 does not do so for
+/// touch screens, so they are currently always `None`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Touch {
+    pub id: u64,
+    pub phase: TouchPhase,
+    pub position: (f64, f64),
+    pub pressure: Option<f64>,
+    pub radius: Option<f64>
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Button {
+    Keyboard(VirtualKeyCode),
+    Mouse(MouseButton)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ButtonState {
+    Pressed,
+    Released
+}
+
+/// A press or release, without the text and lifecycle cases of `Event`.
+///
+/// Unlike `Event` this is `Copy`, so button streams can be filtered, keyed
+/// and fanned out without cloning.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ButtonEvent {
+    pub button: Button,
+    pub state: ButtonState
+}
+
+impl ButtonEvent {
+    pub fn new(button: Button, state: ButtonState) -> ButtonEvent {
+        ButtonEvent { button: button, state: state }
+    }
+
+    pub fn is_press(&self) -> bool {
+        self.state == ButtonState::Pressed
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum Event {
-    Press(()),
-    Release(()),
-    Text(String)
+    Press(Button),
+    Release(Button),
+    /// A mouse press together with its click count: 1 for a single click, 2
+    /// for a double click and so on.
+    Click(MouseButton, u32),
+    Text(String),
+    /// A held key repeating, as emulated with `WindowDriver::set_key_repeat`.
+    Repeat(Button),
+    /// The application was sent to the background. Rendering is paused until
+    /// it is resumed.
+    Suspend,
+    Resume,
+    /// The run loop has ended. This is the last event sent.
+    Shutdown
+}
+
+/// Held modifier keys, left and right separately.
+///
+/// The command key counts as control on macOS, where it is used for the same
+/// shortcuts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct Modifiers {
+    shift: (bool, bool),
+    ctrl: (bool, bool),
+    alt: (bool, bool)
+}
+
+impl Modifiers {
+    pub fn update(self, event: &Event) -> Modifiers {
+        use glutin::VirtualKeyCode::*;
+        let (key, pressed) = match *event {
+            Event::Press(Button::Keyboard(key)) => (key, true),
+            Event::Release(Button::Keyboard(key)) => (key, false),
+            _ => return self
+        };
+        let (left_ctrl, right_ctrl) = if cfg!(target_os = "macos") {
+            (LWin, RWin)
+        } else {
+            (LControl, RControl)
+        };
+        let mut modifiers = self;
+        match key {
+            LShift => modifiers.shift.0 = pressed,
+            RShift => modifiers.shift.1 = pressed,
+            _ if key == left_ctrl => modifiers.ctrl.0 = pressed,
+            _ if key == right_ctrl => modifiers.ctrl.1 = pressed,
+            LAlt => modifiers.alt.0 = pressed,
+            RAlt => modifiers.alt.1 = pressed,
+            _ => ()
+        }
+        modifiers
+    }
+
+    pub fn shift(&self) -> bool {
+        self.shift.0 || self.shift.1
+    }
+
+    pub fn ctrl(&self) -> bool {
+        self.ctrl.0 || self.ctrl.1
+    }
+
+    /// Alt, or Option on macOS. Windows reports AltGr as Ctrl+Alt.
+    pub fn alt(&self) -> bool {
+        self.alt.0 || self.alt.1
+    }
+}
+
+/// Phases of a run loop tick.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FramePhase {
+    Dispatch,
+    Render
+}
+
+/// What the run loop does when the render closure panics.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+pub enum PanicPolicy {
+    /// Let the panic continue unwinding out of the run loop.
+    #[default]
+    Propagate,
+    /// Drop the rest of the frame and keep running.
+    SkipFrame,
+    /// End the run loop as if the window had been closed.
+    Close
+}
+
+/// Scope hooks invoked around each phase of a run loop tick, for integration
+/// with profilers.
+pub trait Profiler {
+    fn begin(&mut self, phase: FramePhase);
+    fn end(&mut self, phase: FramePhase);
+}
+
+/// Timing of a tick that took longer than its budget, all in nanoseconds.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LateFrame {
+    pub budget: u64,
+    pub dispatch: u64,
+    pub render: u64
+}
+
+impl LateFrame {
+    /// How far the tick exceeded its budget.
+    pub fn overrun(&self) -> u64 {
+        (self.dispatch + self.render).saturating_sub(self.budget)
+    }
+
+    /// The phase that took up most of the tick.
+    pub fn phase(&self) -> FramePhase {
+        if self.dispatch > self.render {
+            FramePhase::Dispatch
+        } else {
+            FramePhase::Render
+        }
+    }
 }
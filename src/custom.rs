@@ -0,0 +1,61 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use carboxyl::{Sink, Stream};
+use ::Timed;
+
+/// Injects application events into a driver's dispatch.
+///
+/// Sent values are queued and dispatched by the driver on its next tick,
+/// just before the window events polled during that tick. They receive
+/// sequence numbers from the same counter as window events, so the two stay
+/// consistently ordered. The handle can be cloned and used from any thread.
+#[derive(Clone)]
+pub struct CustomSink<T> {
+    queue: Arc<Mutex<VecDeque<T>>>,
+    sink: Sink<Timed<T>>
+}
+
+impl<T: Clone + Send + Sync + 'static> CustomSink<T> {
+    pub fn new() -> CustomSink<T> {
+        CustomSink { queue: Arc::new(Mutex::new(VecDeque::new())), sink: Sink::new() }
+    }
+
+    /// Queue a value for dispatch on the next tick.
+    pub fn send(&self, value: T) {
+        self.queue.lock().unwrap().push_back(value);
+    }
+
+    pub fn stream(&self) -> Stream<T> {
+        self.sink.stream().map(|timed| timed.value)
+    }
+
+    /// Dispatched values with their dispatch time and sequence number.
+    pub fn timed_stream(&self) -> Stream<Timed<T>> {
+        self.sink.stream()
+    }
+}
+
+impl<T: Clone + Send + Sync + 'static> Default for CustomSink<T> {
+    fn default() -> CustomSink<T> {
+        CustomSink::new()
+    }
+}
+
+/// A queue of injected events that a driver dispatches.
+pub trait Drain {
+    /// Dispatch all queued values, numbering them from `seq` on, and return
+    /// how many there were.
+    fn drain(&self, time: &dyn Fn() -> u64, seq: &mut u64) -> usize;
+}
+
+impl<T: Clone + Send + Sync + 'static> Drain for CustomSink<T> {
+    fn drain(&self, time: &dyn Fn() -> u64, seq: &mut u64) -> usize {
+        let values: Vec<T> = self.queue.lock().unwrap().drain(..).collect();
+        let count = values.len();
+        for value in values {
+            *seq += 1;
+            self.sink.send(Timed::new(time(), *seq, value));
+        }
+        count
+    }
+}
@@ -0,0 +1,68 @@
+/// A value that is held back until its input has been quiet for a while.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Debounce<T> {
+    /// The latest input and the time it arrived at, in nanoseconds.
+    pub pending: Option<(u64, T)>
+}
+
+impl<T: Clone> Debounce<T> {
+    pub fn new() -> Debounce<T> {
+        Debounce { pending: None }
+    }
+
+    /// The pending value, if there was no input for `quiet` nanoseconds
+    /// before `now`.
+    pub fn due(&self, now: u64, quiet: u64) -> Option<T> {
+        match self.pending {
+            Some((time, ref value)) if now.saturating_sub(time) >= quiet =>
+                Some(value.clone()),
+            _ => None
+        }
+    }
+}
+
+#[derive(Clone)]
+pub enum DebounceUpdate<T> {
+    Input(u64, T),
+    Advance(u64)
+}
+
+impl<T: Clone> DebounceUpdate<T> {
+    pub fn apply(self, current: Debounce<T>, quiet: u64) -> Debounce<T> {
+        use self::DebounceUpdate::*;
+        match self {
+            Input(time, value) => Debounce { pending: Some((time, value)) },
+            Advance(now) =>
+                if current.due(now, quiet).is_some() {
+                    Debounce::new()
+                } else {
+                    current
+                }
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::{Debounce, DebounceUpdate};
+    use super::DebounceUpdate::*;
+
+    fn run(updates: Vec<DebounceUpdate<u32>>) -> Debounce<u32> {
+        updates.into_iter()
+            .fold(Debounce::new(), |state, update| update.apply(state, 100))
+    }
+
+    #[test]
+    fn holds_the_latest_input_until_quiet() {
+        let state = run(vec![Input(0, 1), Input(50, 2), Advance(120)]);
+        assert_eq!(state.due(120, 100), None);
+        assert_eq!(state.due(150, 100), Some(2));
+    }
+
+    #[test]
+    fn fires_only_once() {
+        let state = run(vec![Input(0, 1), Advance(100)]);
+        assert_eq!(state.due(200, 100), None);
+    }
+}
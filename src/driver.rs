@@ -1,17 +1,103 @@
+use std::any::Any;
+use std::collections::{HashSet, VecDeque};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Arc;
 use std::thread;
 use std::time::Duration;
 use clock_ticks::precise_time_ns;
 use carboxyl::{Signal, Sink, Stream};
+use carboxyl::lift::{lift2, lift3};
 use glutin;
-use ::{Event, Context};
-use updates::Update;
+use ::{Event, Button, ButtonEvent, ButtonState, Timed, ScrollDelta, Context,
+      WindowProperties, LockKeys, LateFrame, FramePhase, Profiler, PanicPolicy, Modifiers};
+#[cfg(feature = "touch")]
+use ::Touch;
+use clicks::ClickCounter;
+use updates::{Update, CursorUpdate, WindowUpdate};
+use momentum::{Momentum, MomentumUpdate};
+use debounce::{Debounce, DebounceUpdate};
+use hold::{Hold, HoldUpdate};
+use time_combinators::{self, DelayQueue, Interval};
+use idle::{IdlePolicy, nanoseconds};
+use key_repeat::{KeyRepeat, Repeater};
+use compose::Composer;
+use scheduler::{Scheduler, FixedRate, VSync};
+use close::CloseHandle;
+use custom::{CustomSink, Drain};
+use middleware::Middleware;
+use drop_policy::DropPolicy;
+use layers::Layers;
+use letterbox;
+use resize_edge::ResizeEdge;
+use title_bar::{TitleBar, TitleBarLayout};
+use constraints::clamp_size;
+use source::EventSource;
+use metrics::{Metrics, Collector};
+use region::{Rect, Region};
+use router::{Router, PointerEvent, PointerAction};
+#[cfg(feature = "gestures")]
+use gesture::{Gesture, GestureRecognizer, GestureRegistry};
+use focus::FocusManager;
+use replay::{self, Dispatched};
+use latency::{self, LatencyStats};
 
 
-fn state_update(event: glutin::Event) -> Option<Update> {
+/// Default height of a wheel line in pixels.
+const DEFAULT_LINE_HEIGHT: f64 = 20.0;
+
+/// Polling interval of the run loop while rendering is suspended.
+const SUSPENDED_POLL_MS: u64 = 100;
+
+
+fn scroll_delta(delta: glutin::MouseScrollDelta) -> ScrollDelta {
+    use glutin::MouseScrollDelta::*;
+    match delta {
+        LineDelta(x, y) => ScrollDelta::Lines(x as f64, y as f64),
+        PixelDelta(x, y) => ScrollDelta::Pixels(x as f64, y as f64)
+    }
+}
+
+/// Name of an event's kind, for metrics and trace spans.
+fn event_kind(event: &glutin::Event) -> &'static str {
+    use glutin::Event::*;
+    match *event {
+        Resized(..) => "resized",
+        Moved(..) => "moved",
+        Closed => "closed",
+        DroppedFile(..) => "dropped_file",
+        ReceivedCharacter(..) => "received_character",
+        Focused(..) => "focused",
+        KeyboardInput(..) => "keyboard_input",
+        MouseMoved(..) => "mouse_moved",
+        MouseEntered => "mouse_entered",
+        MouseLeft => "mouse_left",
+        MouseWheel(..) => "mouse_wheel",
+        MouseInput(..) => "mouse_input",
+        TouchpadPressure(..) => "touchpad_pressure",
+        Awakened => "awakened",
+        Refresh => "refresh",
+        Suspended(..) => "suspended",
+        Touch(..) => "touch"
+    }
+}
+
+/// Turn a purely vertical scroll into a horizontal one.
+fn horizontal_scroll(delta: ScrollDelta) -> ScrollDelta {
+    match delta {
+        ScrollDelta::Lines(x, y) =>
+            if x == 0.0 { ScrollDelta::Lines(y, 0.0) } else { delta },
+        ScrollDelta::Pixels(x, y) =>
+            if x == 0.0 { ScrollDelta::Pixels(y, 0.0) } else { delta }
+    }
+}
+
+/// The state update for `event`. `repeated` tells whether it is an OS
+/// auto-repeat of a key press, which must not toggle a lock key again.
+fn state_update(event: glutin::Event, repeated: bool) -> Option<Update> {
     use updates::WindowUpdate::*;
-    use updates::CursorUpdate::{self, WheelDelta};
+    use updates::LockKeyUpdate::*;
     use glutin::Event::*;
-    use glutin::MouseScrollDelta;
+    use glutin::{ElementState, VirtualKeyCode};
 
     Some(match event {
         Resized(width, height) =>
@@ -20,67 +106,2165 @@ fn state_update(event: glutin::Event) -> Option<Update> {
             Update::Window(MoveTo(x, y)),
         MouseMoved(x, y) =>
             Update::Cursor(CursorUpdate::MoveTo(x as f64, y as f64)),
-        MouseWheel(MouseScrollDelta::PixelDelta(x, y), _) => // TODO: Implement touch state
-            Update::Cursor(WheelDelta(x as f64, y as f64)),
         Focused(state) =>
             Update::Window(Focus(state)),
+        KeyboardInput(ElementState::Pressed, _, Some(VirtualKeyCode::Capital))
+            if !repeated => Update::LockKeys(ToggleCaps),
+        KeyboardInput(ElementState::Pressed, _, Some(VirtualKeyCode::Numlock))
+            if !repeated => Update::LockKeys(ToggleNum),
         _ => return None
     })
 }
 
+fn input_event(event: &glutin::Event) -> Option<Event> {
+    use glutin::Event::*;
+    use glutin::ElementState::{Pressed, Released};
+
+    Some(match *event {
+        KeyboardInput(Pressed, _, Some(key)) =>
+            Event::Press(Button::Keyboard(key)),
+        KeyboardInput(Released, _, Some(key)) =>
+            Event::Release(Button::Keyboard(key)),
+        MouseInput(Pressed, button) =>
+            Event::Press(Button::Mouse(button)),
+        MouseInput(Released, button) =>
+            Event::Release(Button::Mouse(button)),
+        ReceivedCharacter(c) =>
+            Event::Text(c.to_string()),
+        Suspended(true) =>
+            Event::Suspend,
+        Suspended(false) =>
+            Event::Resume,
+        _ => return None
+    })
+}
+
+fn button_event(event: &glutin::Event) -> Option<ButtonEvent> {
+    use glutin::ElementState::Pressed;
+
+    let (state, button) = match *event {
+        glutin::Event::KeyboardInput(state, _, Some(key)) =>
+            (state, Button::Keyboard(key)),
+        glutin::Event::MouseInput(state, button) =>
+            (state, Button::Mouse(button)),
+        _ => return None
+    };
+    let state = if state == Pressed {
+        ButtonState::Pressed
+    } else {
+        ButtonState::Released
+    };
+    Some(ButtonEvent::new(button, state))
+}
+
+/// Wrap the value of a timed stream for `WindowDriver::dispatched`.
+fn retag<T: 'static>(tag: fn(T) -> Dispatched)
+    -> impl Fn(Timed<T>) -> Timed<Dispatched> + Send + Sync + 'static
+{
+    move |timed| Timed::new(timed.time, timed.seq, tag(timed.value))
+}
+
+/// Clamp a cursor coordinate to the pixels of a window side of `length`.
+fn clamp_to(coordinate: i32, length: u32) -> i32 {
+    let max = (length.min(i32::MAX as u32) as i32 - 1).max(0);
+    coordinate.clamp(0, max)
+}
 
-pub struct WindowDriver {
-    window: glutin::Window,
-    event_sink: Sink<Event>,
-    update_sink: Sink<Update>
+fn is_input(event: &glutin::Event) -> bool {
+    use glutin::Event::*;
+    matches!(*event,
+        KeyboardInput(..) | ReceivedCharacter(_) | MouseInput(..) |
+        MouseMoved(..) | MouseWheel(..) | Touch(_))
 }
 
-impl WindowDriver {
-    pub fn new(window: glutin::Window) -> WindowDriver {
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "render closure panicked".to_string()
+    }
+}
+
+type ShutdownHook<W> = Box<dyn FnOnce(&W)>;
+type Clock = Arc<dyn Fn() -> u64 + Send + Sync>;
+type Timer = Box<dyn FnMut(u64)>;
+
+/// An emulated resize in progress, with the window geometry and screen
+/// cursor position it started from.
+#[derive(Clone, Copy)]
+struct WindowResize {
+    edge: ResizeEdge,
+    anchor: (i32, i32),
+    position: (i32, i32),
+    size: (u32, u32)
+}
+
+
+pub struct WindowDriver<W: EventSource = glutin::Window> {
+    window: W,
+    event_sink: Sink<Timed<Event>>,
+    button_sink: Sink<Timed<ButtonEvent>>,
+    text_sink: Sink<Timed<Arc<str>>>,
+    #[cfg(feature = "unicode-segmentation")]
+    grapheme_sink: Sink<Timed<Arc<str>>>,
+    /// The latest grapheme cluster, which the next character may extend,
+    /// with the time and sequence number of its first character.
+    #[cfg(feature = "unicode-segmentation")]
+    grapheme_buffer: (String, u64, u64),
+    update_sink: Sink<Update>,
+    tick_sink: Sink<u64>,
+    late_sink: Sink<LateFrame>,
+    panic_sink: Sink<String>,
+    wheel_sink: Sink<Timed<ScrollDelta>>,
+    #[cfg(feature = "touch")]
+    touch_sink: Sink<Timed<Touch>>,
+    resize_sink: Sink<Timed<(u32, u32)>>,
+    motion_sink: Sink<Timed<(f64, f64)>>,
+    hover_sink: Sink<bool>,
+    window_cursor_sink: Sink<(f64, f64)>,
+    clamp_cursor: bool,
+    key_repeat: Option<(KeyRepeat, Repeater)>,
+    composer: Option<Composer>,
+    #[cfg(feature = "gestures")]
+    gesture_sink: Sink<Timed<Gesture>>,
+    #[cfg(feature = "gestures")]
+    gestures: GestureRegistry,
+    pressure_sink: Sink<f64>,
+    metrics_sink: Sink<Metrics>,
+    metrics: Collector,
+    line_height: f64,
+    shift_scroll: bool,
+    modifiers: Modifiers,
+    modifiers_sink: Sink<Modifiers>,
+    closed: bool,
+    shut_down: bool,
+    clock: Clock,
+    seq: u64,
+    size: Option<(u32, u32)>,
+    last_resize: Option<(u32, u32)>,
+    min_size: Option<Signal<(u32, u32)>>,
+    max_size: Option<Signal<(u32, u32)>>,
+    resizable: Option<Signal<bool>>,
+    fixed_size: Option<(u32, u32)>,
+    close_handle: CloseHandle,
+    suspended: bool,
+    profiler: Option<Box<dyn Profiler>>,
+    panic_policy: PanicPolicy,
+    shutdown_hooks: Vec<ShutdownHook<W>>,
+    timers: Vec<Timer>,
+    custom_sinks: Vec<Box<dyn Drain>>,
+    middleware: Vec<Box<dyn Middleware>>,
+    drop_policy: Option<DropPolicy>,
+    idle_policy: Option<IdlePolicy>,
+    last_input: u64,
+    held_buttons: u32,
+    held_keys: HashSet<glutin::VirtualKeyCode>,
+    position: (i32, i32),
+    window_drag_sink: Sink<()>,
+    window_drag_requests: Signal<u64>,
+    window_drags_seen: u64,
+    window_drag: Option<(f64, f64)>,
+    window_resize_sink: Sink<ResizeEdge>,
+    window_resize_requests: Signal<(u64, Option<ResizeEdge>)>,
+    window_resizes_seen: u64,
+    window_resize: Option<WindowResize>,
+    cursor: (f64, f64),
+    /// The cursor in window coordinates, as reported before middleware.
+    window_cursor: (f64, f64),
+    clicks: ClickCounter
+}
+
+impl<W: EventSource> WindowDriver<W> {
+    pub fn new(window: W) -> WindowDriver<W> {
+        let size = window.inner_size();
+        let position = window.position().unwrap_or((0, 0));
+        let window_drag_sink = Sink::new();
+        let window_drag_requests = window_drag_sink.stream()
+            .fold(0, |count, ()| count + 1);
+        let window_resize_sink = Sink::new();
+        let window_resize_requests = window_resize_sink.stream()
+            .fold((0, None), |(count, _), edge| (count + 1, Some(edge)));
         WindowDriver {
             window: window,
             event_sink: Sink::new(),
-            update_sink: Sink::new()
-        }
-    }
-
-    pub fn run_with<F: FnMut(&glutin::Window)>(&mut self, fps: f64, mut render: F) {
-        assert!(fps > 0.0);
-        let tick_length = (1e9 / fps) as u64;
-        let mut time = precise_time_ns();
-        let mut next_tick = time;
-        let mut should_close = false;
-        while !should_close {
-            time = precise_time_ns();
-            if time >= next_tick {
-                let diff = time - next_tick;
-                let delta = diff - diff % tick_length;
-                next_tick += delta;
-                for event in self.window.poll_events() {
-                    if let glutin::Event::Closed = event {
-                        should_close = true;
-                    }
-                    self.dispatch(event)
+            button_sink: Sink::new(),
+            text_sink: Sink::new(),
+            #[cfg(feature = "unicode-segmentation")]
+            grapheme_sink: Sink::new(),
+            #[cfg(feature = "unicode-segmentation")]
+            grapheme_buffer: (String::new(), 0, 0),
+            update_sink: Sink::new(),
+            tick_sink: Sink::new(),
+            late_sink: Sink::new(),
+            panic_sink: Sink::new(),
+            wheel_sink: Sink::new(),
+            #[cfg(feature = "touch")]
+            touch_sink: Sink::new(),
+            resize_sink: Sink::new(),
+            motion_sink: Sink::new(),
+            hover_sink: Sink::new(),
+            window_cursor_sink: Sink::new(),
+            clamp_cursor: false,
+            key_repeat: None,
+            composer: None,
+            #[cfg(feature = "gestures")]
+            gesture_sink: Sink::new(),
+            #[cfg(feature = "gestures")]
+            gestures: GestureRegistry::new(),
+            pressure_sink: Sink::new(),
+            metrics_sink: Sink::new(),
+            metrics: Collector::new(),
+            line_height: DEFAULT_LINE_HEIGHT,
+            shift_scroll: !cfg!(target_os = "macos"),
+            modifiers: Modifiers::default(),
+            modifiers_sink: Sink::new(),
+            closed: false,
+            shut_down: false,
+            clock: Arc::new(precise_time_ns),
+            seq: 0,
+            size: size,
+            last_resize: None,
+            min_size: None,
+            max_size: None,
+            resizable: None,
+            fixed_size: None,
+            close_handle: CloseHandle::new(),
+            suspended: false,
+            profiler: None,
+            panic_policy: PanicPolicy::default(),
+            shutdown_hooks: Vec::new(),
+            timers: Vec::new(),
+            custom_sinks: Vec::new(),
+            middleware: Vec::new(),
+            drop_policy: None,
+            idle_policy: None,
+            last_input: 0,
+            held_buttons: 0,
+            held_keys: HashSet::new(),
+            position: position,
+            window_drag_sink: window_drag_sink,
+            window_drag_requests: window_drag_requests,
+            window_drags_seen: 0,
+            window_drag: None,
+            window_resize_sink: window_resize_sink,
+            window_resize_requests: window_resize_requests,
+            window_resizes_seen: 0,
+            window_resize: None,
+            cursor: (0.0, 0.0),
+            window_cursor: (0.0, 0.0),
+            clicks: ClickCounter::default()
+        }
+    }
+
+    /// Replace the clock used to timestamp events, which returns nanoseconds.
+    /// The run loop schedules ticks by it as well, so a manual clock is only
+    /// useful together with `step`.
+    pub fn set_clock<C: Fn() -> u64 + Send + Sync + 'static>(&mut self, clock: C) {
+        self.clock = Arc::new(clock);
+    }
+
+    fn now(&self) -> u64 {
+        (self.clock)()
+    }
+
+    /// Set the maximum time between presses that still counts as a
+    /// multi-click. The backend does not expose the platform setting, so this
+    /// defaults to 500 ms.
+    pub fn set_double_click_interval(&mut self, interval: Duration) {
+        self.clicks.set_interval(nanoseconds(interval));
+    }
+
+    /// Set how many pixels a line-based wheel step scrolls in the cursor's
+    /// accumulated wheel position (20 by default).
+    pub fn set_line_height(&mut self, line_height: f64) {
+        self.line_height = line_height;
+    }
+
+    /// Choose whether vertical scrolling with Shift held is reported as
+    /// horizontal scrolling. This is on by default, except on macOS where the
+    /// system already does so.
+    pub fn set_shift_scrolls_horizontally(&mut self, enabled: bool) {
+        self.shift_scroll = enabled;
+    }
+
+    /// Install hooks that are called at the beginning and end of event
+    /// dispatch and rendering in every tick.
+    ///
+    /// For puffin, enable the `profiling` feature instead. Every tick then
+    /// starts a puffin frame with scopes for polling, rendering, sleeping and
+    /// each dispatched event, named by its kind. Scopes are only recorded
+    /// once `puffin::set_scopes_on(true)` has been called.
+    pub fn set_profiler<P: Profiler + 'static>(&mut self, profiler: P) {
+        self.profiler = Some(Box::new(profiler));
+    }
+
+    /// Keep the window at least as large as the sampled size. Limits are
+    /// applied on every tick by resizing the window back into range.
+    pub fn bind_min_size(&mut self, size: Signal<(u32, u32)>) {
+        self.min_size = Some(size);
+    }
+
+    /// Keep the window at most as large as the sampled size, see
+    /// `bind_min_size`.
+    pub fn bind_max_size(&mut self, size: Signal<(u32, u32)>) {
+        self.max_size = Some(size);
+    }
+
+    /// Pin the window to its current size while the signal is false.
+    ///
+    /// glutin cannot change the resizable flag of an existing window, so the
+    /// system still offers resizing, but the window snaps back on the next
+    /// tick. Window decorations cannot be toggled at runtime at all.
+    pub fn bind_resizable(&mut self, resizable: Signal<bool>) {
+        self.resizable = Some(resizable);
+    }
+
+    /// A trigger that starts moving the window with the cursor.
+    ///
+    /// Send to it while a mouse button is held, e.g. in reaction to a press
+    /// on a custom title bar. The window then follows the cursor until all
+    /// buttons are released. The move is emulated by repositioning the
+    /// window, since glutin cannot hand the drag over to the system.
+    pub fn begin_window_drag(&self) -> Sink<()> {
+        self.window_drag_sink.clone()
+    }
+
+    /// A trigger that starts resizing the window from an edge with the cursor.
+    ///
+    /// Like `begin_window_drag`, send to it while a mouse button is held,
+    /// e.g. with the edge from `resize_edge` on a press. The resize is
+    /// emulated until all buttons are released.
+    pub fn begin_window_resize(&self) -> Sink<ResizeEdge> {
+        self.window_resize_sink.clone()
+    }
+
+    /// The resize edge under the cursor, for edges `border` pixels wide.
+    /// Useful to set the cursor icon on borderless windows.
+    ///
+    /// The cursor is hit-tested in window coordinates, before middleware such
+    /// as `Letterbox` maps it into a viewport.
+    pub fn resize_edge(&self, border: f64) -> Signal<Option<ResizeEdge>> {
+        lift2(move |cursor, size| ResizeEdge::at(cursor, size, border),
+              &self.window_cursor(), &self.size())
+    }
+
+    /// Edges that the left mouse button was pressed on, to feed into
+    /// `begin_window_resize`.
+    pub fn resize_drags(&self, border: f64) -> Stream<ResizeEdge> {
+        self.resize_edge(border)
+            .snapshot(&self.events(), |edge, event| match event {
+                Event::Press(Button::Mouse(glutin::MouseButton::Left)) => edge,
+                _ => None
+            })
+            .filter_some()
+    }
+
+    /// A handle to end the run loop from elsewhere, e.g. a signal handler.
+    pub fn close_handle(&self) -> CloseHandle {
+        self.close_handle.clone()
+    }
+
+    /// Choose how panics in the render closure are handled. Either way, their
+    /// message is sent on `render_panics` first.
+    pub fn set_panic_policy(&mut self, policy: PanicPolicy) {
+        self.panic_policy = policy;
+    }
+
+    /// Register a hook that runs once the run loop has ended, after the
+    /// `Shutdown` event has been sent. Hooks run in registration order.
+    pub fn on_shutdown<F: FnOnce(&W) + 'static>(&mut self, hook: F) {
+        self.shutdown_hooks.push(Box::new(hook));
+    }
+
+    /// Lower the frame rate of `run_with` while there is no input, or pass
+    /// `None` to always run at full rate.
+    pub fn set_idle_policy(&mut self, policy: Option<IdlePolicy>) {
+        self.idle_policy = policy;
+    }
+
+    pub fn run_with<F: FnMut(&W)>(&mut self, fps: f64, render: F) {
+        let scheduler = FixedRate::new(fps, self.idle_policy);
+        self.run_scheduled(scheduler, render);
+    }
+
+    /// Run the event loop with one tick per presented frame, see `VSync`.
+    pub fn run_vsync<F: FnMut(&W)>(&mut self, render: F) {
+        self.run_scheduled(VSync, render);
+    }
+
+    /// Run the event loop with a custom timing policy.
+    pub fn run_scheduled<S, F>(&mut self, mut scheduler: S, mut render: F)
+        where S: Scheduler, F: FnMut(&W)
+    {
+        let mut running = true;
+        self.last_input = self.now();
+        while running {
+            let time = self.now();
+            if scheduler.due(time, self.last_input) {
+                let budget = scheduler.budget();
+                running = self.tick(time, budget, &mut render);
+                if self.suspended {
+                    #[cfg(feature = "profiling")]
+                    ::puffin::profile_scope!("sleep");
+                    thread::sleep(Duration::from_millis(SUSPENDED_POLL_MS));
                 }
-                render(&self.window);
             } else {
-                thread::sleep(Duration::from_millis((next_tick - time) as u64));
+                #[cfg(feature = "profiling")]
+                ::puffin::profile_scope!("sleep");
+                scheduler.wait(time);
             }
         }
+        self.shutdown();
+    }
+
+    /// Send the `Shutdown` event and run the shutdown hooks, unless that has
+    /// happened already.
+    pub(crate) fn shutdown(&mut self) {
+        if self.shut_down {
+            return;
+        }
+        self.shut_down = true;
+        #[cfg(feature = "unicode-segmentation")]
+        self.flush_grapheme();
+        self.seq += 1;
+        let time = self.now();
+        self.event_sink.send(Timed::new(time, self.seq, Event::Shutdown));
+        for hook in self.shutdown_hooks.drain(..) {
+            hook(&self.window);
+        }
     }
 
-    fn dispatch(&self, event: glutin::Event) {
-        if let Some(update) = state_update(event) {
+    /// Perform a single tick without rendering or sleeping.
+    ///
+    /// This polls pending events, dispatches them and then sends `now` (in
+    /// nanoseconds) on the tick stream. It returns whether the window should
+    /// stay open, which makes it usable from an outer loop or a test harness.
+    /// When it first returns `false`, the `Shutdown` event has been sent and
+    /// the shutdown hooks have run, as at the end of the run loop.
+    pub fn step(&mut self, now: u64) -> bool {
+        let running = self.advance(now);
+        if !running {
+            self.shutdown();
+        }
+        running
+    }
+
+    /// `step` without shutting down, which the run loop does after rendering.
+    fn advance(&mut self, now: u64) -> bool {
+        if let Some(metrics) = self.metrics.roll(now) {
+            self.metrics_sink.send(metrics);
+        }
+        self.pump();
+        if self.close_handle.is_requested() {
+            self.closed = true;
+        }
+        self.apply_size_limits();
+        self.poll_window_drag();
+        self.poll_window_resize();
+        self.emit_repeats(now);
+        for timer in &mut self.timers {
+            timer(now);
+        }
+        self.tick_sink.send(now);
+        !self.closed
+    }
+
+    /// Dispatch all pending events without ticking or rendering and return
+    /// how many there were.
+    ///
+    /// This keeps the window responsive during long blocking operations. A
+    /// close request seen here still ends the run loop on its next tick.
+    pub fn poll_and_dispatch(&mut self) -> usize {
+        self.pump()
+    }
+
+    fn apply_size_limits(&mut self) {
+        let resizable = self.resizable.as_ref().is_none_or(|r| r.sample());
+        self.fixed_size = if resizable { None } else { self.fixed_size.or(self.size) };
+        if self.min_size.is_none() && self.max_size.is_none() && resizable {
+            return;
+        }
+        if let Some(size) = self.size {
+            let (min, max) = match self.fixed_size {
+                Some(fixed) => (fixed, fixed),
+                None => (
+                    self.min_size.as_ref().map_or((0, 0), |min| min.sample()),
+                    self.max_size.as_ref()
+                        .map_or((u32::MAX, u32::MAX), |max| max.sample())
+                )
+            };
+            let clamped = clamp_size(size, min, max);
+            if clamped != size {
+                self.window.set_inner_size(clamped.0, clamped.1);
+                self.size = Some(clamped);
+            }
+        }
+    }
+
+    fn pump(&mut self) -> usize {
+        let mut count = 0;
+        for custom in &self.custom_sinks {
+            let clock = &self.clock;
+            count += custom.drain(&|| clock(), &mut self.seq);
+        }
+        let mut events = {
+            #[cfg(feature = "profiling")]
+            ::puffin::profile_scope!("poll");
+            self.window.poll_events()
+        };
+        count += events.len();
+        if let Some(ref policy) = self.drop_policy {
+            events = policy.coalesce_batch(events);
+        }
+        if let Some(ref mut composer) = self.composer {
+            events = composer.compose_events(events);
+        }
+        for event in events {
+            match event {
+                glutin::Event::Closed => self.closed = true,
+                // Window chrome follows the cursor in window coordinates,
+                // whatever middleware makes of it
+                glutin::Event::MouseMoved(x, y) => {
+                    self.window_cursor = (x as f64, y as f64);
+                    self.window_cursor_sink.send(self.window_cursor);
+                },
+                _ => ()
+            }
+            let event = match self.middleware.iter_mut()
+                .try_fold(event, |event, middleware| middleware.process(event))
+            {
+                Some(event) => event,
+                None => continue
+            };
+            let time = self.now();
+            if self.is_backend_repeat(&event, time) {
+                continue;
+            }
+            self.dispatch(event, time)
+        }
+        count
+    }
+
+    /// Add a received character to the latest grapheme cluster, sending the
+    /// cluster once the character turns out to start the next one.
+    #[cfg(feature = "unicode-segmentation")]
+    fn feed_grapheme(&mut self, c: char, time: u64, seq: u64) {
+        use unicode_segmentation::UnicodeSegmentation;
+        if self.grapheme_buffer.0.is_empty() {
+            self.grapheme_buffer = (String::new(), time, seq);
+        }
+        self.grapheme_buffer.0.push(c);
+        let next = self.grapheme_buffer.0.grapheme_indices(true)
+            .nth(1)
+            .map(|(index, _)| index);
+        if let Some(index) = next {
+            let rest = self.grapheme_buffer.0.split_off(index);
+            self.flush_grapheme();
+            self.grapheme_buffer = (rest, time, seq);
+        }
+    }
+
+    /// Send the latest grapheme cluster, which is complete once anything but
+    /// text follows.
+    #[cfg(feature = "unicode-segmentation")]
+    fn flush_grapheme(&mut self) {
+        let (text, time, seq) = ::std::mem::take(&mut self.grapheme_buffer);
+        if !text.is_empty() {
+            self.grapheme_sink.send(Timed::new(time, seq, text.into()));
+        }
+    }
+
+    /// Step and render once. Returns whether the loop should keep running.
+    fn tick<F: FnMut(&W)>(&mut self, time: u64, budget: Option<u64>,
+                          render: &mut F)
+        -> bool
+    {
+        #[cfg(feature = "tracing")]
+        let _span = ::tracing::trace_span!("tick", time = time).entered();
+        #[cfg(feature = "profiling")]
+        ::puffin::GlobalProfiler::lock().new_frame();
+        #[cfg(feature = "profiling")]
+        ::puffin::profile_scope!("tick");
+        let start = precise_time_ns();
+        self.begin_phase(FramePhase::Dispatch);
+        let mut running = self.advance(time);
+        self.end_phase(FramePhase::Dispatch);
+        let dispatched = precise_time_ns();
+        if !self.suspended {
+            self.begin_phase(FramePhase::Render);
+            let window = &self.window;
+            let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                #[cfg(feature = "tracing")]
+                let _span = ::tracing::trace_span!("render").entered();
+                #[cfg(feature = "profiling")]
+                ::puffin::profile_scope!("render");
+                render(window)
+            }));
+            self.end_phase(FramePhase::Render);
+            if let Err(payload) = result {
+                self.panic_sink.send(panic_message(&*payload));
+                match self.panic_policy {
+                    PanicPolicy::Propagate => panic::resume_unwind(payload),
+                    PanicPolicy::SkipFrame => (),
+                    PanicPolicy::Close => {
+                        self.closed = true;
+                        running = false;
+                    }
+                }
+            }
+        }
+        let rendered = precise_time_ns();
+        if !self.suspended {
+            self.metrics.frame(dispatched - start, rendered - dispatched);
+        }
+        if let Some(budget) = budget {
+            let frame = LateFrame {
+                budget: budget,
+                dispatch: dispatched - start,
+                render: rendered - dispatched
+            };
+            if frame.overrun() > 0 {
+                self.late_sink.send(frame);
+            }
+        }
+        running
+    }
+
+    fn begin_phase(&mut self, phase: FramePhase) {
+        if let Some(ref mut profiler) = self.profiler {
+            profiler.begin(phase);
+        }
+    }
+
+    fn end_phase(&mut self, phase: FramePhase) {
+        if let Some(ref mut profiler) = self.profiler {
+            profiler.end(phase);
+        }
+    }
+
+    fn dispatch(&mut self, event: glutin::Event, time: u64) {
+        use glutin::ElementState::Pressed;
+        self.seq += 1;
+        let seq = self.seq;
+        #[cfg(feature = "tracing")]
+        let _span = ::tracing::trace_span!(
+            "dispatch", kind = event_kind(&event), seq = seq).entered();
+        #[cfg(feature = "profiling")]
+        ::puffin::profile_scope!("dispatch", event_kind(&event));
+        self.metrics.count(event_kind(&event));
+        #[cfg(feature = "unicode-segmentation")]
+        match event {
+            glutin::Event::ReceivedCharacter(c) => self.feed_grapheme(c, time, seq),
+            _ => self.flush_grapheme()
+        }
+        if let glutin::Event::MouseMoved(..) = event {
+            // X11 and macOS keep reporting motion outside of the window while
+            // a button is held
+            let (width, height) = self.size.unwrap_or((u32::MAX, u32::MAX));
+            let (x, y) = self.window_cursor;
+            self.hover_sink.send(x >= 0.0 && y >= 0.0 &&
+                                 x < width as f64 && y < height as f64);
+        }
+        let event = match (event, self.size) {
+            (glutin::Event::MouseMoved(x, y), Some(size)) if self.clamp_cursor =>
+                glutin::Event::MouseMoved(clamp_to(x, size.0), clamp_to(y, size.1)),
+            (event, _) => event
+        };
+        if is_input(&event) {
+            self.last_input = time;
+        }
+        let repeated = self.track_key(&event);
+        if let Some(event) = input_event(&event) {
+            self.set_modifiers(self.modifiers.update(&event));
+            self.event_sink.send(Timed::new(time, seq, event));
+        }
+        if let Some(button) = button_event(&event) {
+            self.button_sink.send(Timed::new(time, seq, button));
+        }
+        if let glutin::Event::ReceivedCharacter(c) = event {
+            let text: Arc<str> = c.to_string().into();
+            self.text_sink.send(Timed::new(time, seq, text));
+        }
+        match event {
+            glutin::Event::MouseInput(state, button) => {
+                self.track_drag(state);
+                if state == Pressed {
+                    self.recognize(time, seq, PointerAction::Press(button));
+                    let count = self.clicks.press(button, time, self.cursor);
+                    let click = Event::Click(button, count);
+                    self.event_sink.send(Timed::new(time, seq, click));
+                } else {
+                    self.recognize(time, seq, PointerAction::Release(button));
+                }
+            },
+            glutin::Event::MouseMoved(x, y) => {
+                // Anchor a resize requested since the last motion before it
+                self.poll_window_resize();
+                self.cursor = (x as f64, y as f64);
+                self.motion_sink.send(Timed::new(time, seq, self.cursor));
+                self.recognize(time, seq, PointerAction::Move);
+                self.poll_window_drag();
+                self.drag_window();
+                self.resize_window();
+            },
+            glutin::Event::Moved(x, y) =>
+                self.position = (x, y),
+            glutin::Event::MouseWheel(delta, _) => // TODO: Implement touch state
+                self.scroll(time, seq, scroll_delta(delta)),
+            glutin::Event::Resized(width, height) => {
+                self.size = Some((width, height));
+                // Backends may repeat a resize; only propagate changes
+                if self.last_resize == Some((width, height)) {
+                    return;
+                }
+                self.last_resize = Some((width, height));
+                self.resize_sink.send(Timed::new(time, seq, (width, height)));
+            },
+            glutin::Event::Suspended(state) =>
+                self.suspended = state,
+            #[cfg(feature = "touch")]
+            glutin::Event::Touch(touch) =>
+                self.touch_sink.send(Timed::new(time, seq, Touch {
+                    id: touch.id,
+                    phase: touch.phase,
+                    position: touch.location,
+                    pressure: None,
+                    radius: None
+                })),
+            glutin::Event::TouchpadPressure(pressure, _) =>
+                self.pressure_sink.send(pressure as f64),
+            glutin::Event::MouseEntered =>
+                self.hover_sink.send(true),
+            glutin::Event::MouseLeft =>
+                self.hover_sink.send(false),
+            glutin::Event::Focused(false) => {
+                self.hover_sink.send(false);
+                self.set_modifiers(Modifiers::default());
+                self.held_keys.clear();
+                self.release_drag();
+                self.clicks.reset();
+            },
+            _ => ()
+        }
+        if let Some(update) = state_update(event, repeated) {
             self.update_sink.send(update);
         }
     }
 
+    /// Track held keys and tell whether `event` presses a key that is
+    /// already held, as the backend's auto-repeat does.
+    fn track_key(&mut self, event: &glutin::Event) -> bool {
+        use glutin::ElementState::{Pressed, Released};
+        match *event {
+            glutin::Event::KeyboardInput(Pressed, _, Some(key)) =>
+                !self.held_keys.insert(key),
+            glutin::Event::KeyboardInput(Released, _, Some(key)) => {
+                self.held_keys.remove(&key);
+                false
+            },
+            _ => false
+        }
+    }
+
+    fn set_modifiers(&mut self, modifiers: Modifiers) {
+        if modifiers != self.modifiers {
+            self.modifiers = modifiers;
+            self.modifiers_sink.send(modifiers);
+        }
+    }
+
+    #[cfg(not(feature = "gestures"))]
+    fn recognize(&mut self, _time: u64, _seq: u64, _action: PointerAction) {}
+
+    #[cfg(feature = "gestures")]
+    fn recognize(&mut self, time: u64, seq: u64, action: PointerAction) {
+        let event = Timed::new(time, seq, PointerEvent::new(self.cursor, action));
+        if let Some(gesture) = self.gestures.feed(&event) {
+            self.gesture_sink.send(Timed::new(time, seq, gesture));
+        }
+    }
+
+    fn scroll(&self, time: u64, seq: u64, delta: ScrollDelta) {
+        let delta = if self.shift_scroll && self.modifiers.shift() {
+            horizontal_scroll(delta)
+        } else {
+            delta
+        };
+        self.wheel_sink.send(Timed::new(time, seq, delta));
+        let (x, y) = delta.to_pixels(self.line_height);
+        self.update_sink.send(Update::Cursor(CursorUpdate::WheelDelta(x, y)));
+    }
+
+    fn track_drag(&mut self, state: glutin::ElementState) {
+        use glutin::ElementState;
+        self.held_buttons = match state {
+            ElementState::Pressed => self.held_buttons + 1,
+            ElementState::Released => self.held_buttons.saturating_sub(1)
+        };
+        if self.held_buttons == 0 {
+            self.window_drag = None;
+            self.window_resize = None;
+        }
+    }
+
+    fn poll_window_drag(&mut self) {
+        let requests = self.window_drag_requests.sample();
+        if requests != self.window_drags_seen {
+            self.window_drags_seen = requests;
+            if self.held_buttons > 0 {
+                self.window_drag = Some(self.window_cursor);
+            }
+        }
+    }
+
+    /// Track keys for repeat emulation and tell whether `event` is a repeat
+    /// sent by the backend, or text typed by one, which the emulation
+    /// replaces.
+    fn is_backend_repeat(&mut self, event: &glutin::Event, time: u64) -> bool {
+        use glutin::ElementState::{Pressed, Released};
+        let (config, repeater) = match self.key_repeat {
+            Some((ref config, ref mut repeater)) => (config, repeater),
+            None => return false
+        };
+        match *event {
+            glutin::Event::KeyboardInput(Pressed, _, Some(key)) =>
+                return !repeater.press(key, time, config),
+            glutin::Event::KeyboardInput(Released, _, Some(key)) =>
+                repeater.release(key),
+            glutin::Event::ReceivedCharacter(c) =>
+                return repeater.character(c),
+            glutin::Event::Focused(false) =>
+                repeater.reset(),
+            _ => repeater.interrupt()
+        }
+        false
+    }
+
+    /// Send the repeats due at `now`, each followed by the text its key
+    /// typed when pressed.
+    fn emit_repeats(&mut self, now: u64) {
+        let due = match self.key_repeat {
+            Some((ref config, ref mut repeater)) => repeater.due(now, config)
+                .map(|(key, count)| (key, count, repeater.text().to_string())),
+            None => None
+        };
+        if let Some((key, count, text)) = due {
+            for _ in 0..count {
+                self.seq += 1;
+                let repeat = Event::Repeat(Button::Keyboard(key));
+                self.event_sink.send(Timed::new(now, self.seq, repeat));
+                for c in text.chars() {
+                    self.dispatch(glutin::Event::ReceivedCharacter(c), now);
+                }
+            }
+        }
+    }
+
+    fn poll_window_resize(&mut self) {
+        let (requests, edge) = self.window_resize_requests.sample();
+        if requests != self.window_resizes_seen {
+            self.window_resizes_seen = requests;
+            if let (Some(edge), Some(size)) = (edge, self.size) {
+                if self.held_buttons > 0 {
+                    self.window_resize = Some(WindowResize {
+                        edge: edge,
+                        anchor: self.screen_cursor(),
+                        position: self.position,
+                        size: size
+                    });
+                }
+            }
+        }
+    }
+
+    /// The cursor relative to the screen, which unlike the reported position
+    /// does not change when the window moves.
+    fn screen_cursor(&self) -> (i32, i32) {
+        let (x, y) = self.window_cursor;
+        (self.position.0 + x as i32, self.position.1 + y as i32)
+    }
+
+    fn resize_window(&mut self) {
+        if let Some(resize) = self.window_resize {
+            let cursor = self.screen_cursor();
+            let delta = (cursor.0 - resize.anchor.0, cursor.1 - resize.anchor.1);
+            let (position, size) =
+                resize.edge.resize(resize.position, resize.size, delta);
+            if position != self.position {
+                self.window.set_position(position.0, position.1);
+                self.position = position;
+            }
+            if Some(size) != self.size {
+                self.window.set_inner_size(size.0, size.1);
+            }
+        }
+    }
+
+    fn drag_window(&mut self) {
+        if let Some(anchor) = self.window_drag {
+            let x = self.position.0 + (self.window_cursor.0 - anchor.0) as i32;
+            let y = self.position.1 + (self.window_cursor.1 - anchor.1) as i32;
+            if (x, y) != self.position {
+                self.window.set_position(x, y);
+                self.position = (x, y);
+            }
+        }
+    }
+
+    fn release_drag(&mut self) {
+        self.window_drag = None;
+        self.window_resize = None;
+        self.held_buttons = 0;
+    }
+
+    /// The window's context, starting out with the size and position the
+    /// window reports, so that it is right before the first resize as well.
     pub fn context(&self) -> Signal<Context> {
         self.update_sink.stream()
-            .fold(Context::default(), |old, update| update.apply(old))
+            .fold(self.initial_context(), |old, update| update.apply(old))
+    }
+
+    fn initial_context(&self) -> Context {
+        let size = self.size.unwrap_or((0, 0));
+        let window = WindowProperties::new(self.position, size, true);
+        Context { window: window, .. Context::default() }
+    }
+
+    pub fn lock_keys(&self) -> Signal<LockKeys> {
+        self.context().map(|context| context.lock_keys)
+    }
+
+    /// Held modifier keys, tracked from all of the window's keyboard events.
+    /// They are all released when the window loses focus.
+    ///
+    /// Pass this to `text_field` and `undo_history`, which only see the
+    /// events of their widget.
+    pub fn modifiers(&self) -> Signal<Modifiers> {
+        self.modifiers_sink.stream().hold(self.modifiers)
+    }
+
+    /// The window size, which only changes when the size actually does.
+    pub fn size(&self) -> Signal<(u32, u32)> {
+        self.context().map(|context| context.window.size)
+    }
+
+    /// Width over height of the window.
+    ///
+    /// This is 1 while either side is zero, as for a source that reports no
+    /// size before its first resize, so projections derived from it stay
+    /// finite.
+    pub fn aspect_ratio(&self) -> Signal<f64> {
+        self.size().map(|(width, height)| {
+            if width == 0 || height == 0 {
+                1.0
+            } else {
+                width as f64 / height as f64
+            }
+        })
+    }
+
+    /// The viewport of aspect ratio `aspect` centered in the window, with bars
+    /// on the remaining sides. Pair it with the `Letterbox` middleware to get
+    /// cursor positions within it.
+    pub fn letterbox(&self, aspect: f64) -> Signal<Rect> {
+        self.size().map(move |size| letterbox::letterbox(size, aspect))
+    }
+
+    /// The cursor position clamped to the window bounds, in window
+    /// coordinates, before middleware such as `Letterbox`.
+    pub fn cursor_clamped(&self) -> Signal<(f64, f64)> {
+        lift2(|(x, y): (f64, f64), (width, height): (u32, u32)| {
+            (x.clamp(0.0, (width as f64 - 1.0).max(0.0)),
+             y.clamp(0.0, (height as f64 - 1.0).max(0.0)))
+        }, &self.window_cursor(), &self.size())
+    }
+
+    /// The cursor in window coordinates, as the backend reports it.
+    fn window_cursor(&self) -> Signal<(f64, f64)> {
+        self.window_cursor_sink.stream().hold(self.window_cursor)
+    }
+
+    /// Whether the cursor is over the window.
+    ///
+    /// This turns false when the cursor leaves or the window loses focus,
+    /// without waiting for further motion, and true again once the cursor
+    /// enters or moves inside. It starts out false.
+    pub fn cursor_in_window(&self) -> Signal<bool> {
+        self.hover_sink.stream().hold(false)
+    }
+
+    /// Window resizes, without repeated reports of the same size.
+    pub fn resizes(&self) -> Stream<(u32, u32)> {
+        self.resize_sink.stream().map(|timed| timed.value)
+    }
+
+    /// Window resizes with their dispatch time and sequence number.
+    pub fn timed_resizes(&self) -> Stream<Timed<(u32, u32)>> {
+        self.resize_sink.stream()
+    }
+
+    /// Fire once on the first tick at least `duration` from now.
+    pub fn after(&self, duration: Duration) -> Stream<u64> {
+        time_combinators::after(&self.ticks(), self.now() + nanoseconds(duration))
+    }
+
+    /// Fire once per `period`, starting one period from now.
+    ///
+    /// Each firing carries the time the period was scheduled for. Periods
+    /// are checked on every tick, and a tick that comes late fires once for
+    /// every period it missed.
+    pub fn every(&mut self, period: Duration) -> Stream<u64> {
+        let mut interval = Interval::new(self.now(), nanoseconds(period));
+        let sink = Sink::new();
+        let periods = sink.stream();
+        self.timers.push(Box::new(move |now| {
+            for time in interval.advance(now) {
+                sink.send(time);
+            }
+        }));
+        periods
+    }
+
+    /// Release the values of `stream` again on the first tick `duration`
+    /// after they arrived.
+    ///
+    /// Values are timed by the driver's clock as they arrive, also between
+    /// ticks. All values due on a tick are released during it, in their
+    /// original order.
+    pub fn delay<T>(&mut self, stream: &Stream<T>, duration: Duration) -> Stream<T>
+        where T: Clone + Send + Sync + 'static
+    {
+        let duration = nanoseconds(duration);
+        let ticks = self.ticks();
+        let clock = self.clock.clone();
+        let pushes = stream.map(move |value| (Some((clock() + duration, value)), 0));
+        let queue = pushes.merge(&ticks.map(|now| (None, now)))
+            .fold(DelayQueue::new(), |queue, update| match update {
+                (Some((due, value)), _) => queue.push(due, value),
+                (None, now) => queue.advance(now)
+            });
+        let sink = Sink::new();
+        let released = sink.stream();
+        // Runs before the tick is sent, which drops the released values
+        self.timers.push(Box::new(move |now| {
+            for value in queue.sample().due(now) {
+                sink.send(value);
+            }
+        }));
+        released
+    }
+
+    /// The window size once it has stopped changing for `quiet`.
+    ///
+    /// Intermediate sizes of a drag-resize are skipped. Time is measured by
+    /// the driver's clock and checked on every tick, so the stream fires on
+    /// the first tick after the quiet period.
+    pub fn resize_settled(&self, quiet: Duration) -> Stream<(u32, u32)> {
+        let quiet = nanoseconds(quiet);
+        let inputs = self.timed_resizes()
+            .map(|resize| DebounceUpdate::Input(resize.time, resize.value));
+        let advances = self.ticks().map(DebounceUpdate::Advance);
+        // Snapshots see the state from before the tick is folded in
+        inputs.merge(&advances)
+            .fold(Debounce::new(), move |old, update| update.apply(old, quiet))
+            .snapshot(&self.ticks(), move |state, now| state.due(now, quiet))
+            .filter_map(|size| size)
+    }
+
+    /// Time in nanoseconds since `button` was pressed, or zero while it is
+    /// released.
+    ///
+    /// This is updated on every tick. Repeated presses from key repeat do not
+    /// restart the measurement. Losing focus counts as a release, since the
+    /// backend does not report releases that happen while unfocused.
+    pub fn held_duration(&self, button: Button) -> Signal<u64> {
+        let presses = self.timed_events()
+            .filter_map(move |event| match event.value {
+                Event::Press(pressed) if pressed == button =>
+                    Some(HoldUpdate::Press(event.time)),
+                Event::Release(released) if released == button =>
+                    Some(HoldUpdate::Release),
+                _ => None
+            });
+        let blurs = self.update_sink.stream()
+            .filter_map(|update| match update {
+                Update::Window(WindowUpdate::Focus(false)) => Some(HoldUpdate::Release),
+                _ => None
+            });
+        presses.merge(&blurs)
+            .merge(&self.ticks().map(HoldUpdate::Advance))
+            .fold(Hold::default(), |old, update| update.apply(old))
+            .map(|hold| hold.duration)
+    }
+
+    /// Cursor positions with their dispatch time and sequence number.
+    ///
+    /// Whether motion is still reported once a drag leaves the window is up
+    /// to the platform. X11 and macOS keep reporting it while a button is
+    /// held, but glutin offers no way to capture the pointer elsewhere; its
+    /// `CursorState::Grab` confines the pointer to the window instead.
+    pub fn timed_cursor_moves(&self) -> Stream<Timed<(f64, f64)>> {
+        self.motion_sink.stream()
+    }
+
+    /// The last `capacity` cursor positions, oldest first.
+    pub fn cursor_history(&self, capacity: usize)
+        -> Signal<VecDeque<Timed<(f64, f64)>>>
+    {
+        assert!(capacity > 0);
+        self.motion_sink.stream()
+            .fold(VecDeque::with_capacity(capacity), move |mut history, position| {
+                if history.len() == capacity {
+                    history.pop_front();
+                }
+                history.push_back(position);
+                history
+            })
+    }
+
+    /// Hit-test the cursor against `rect`.
+    ///
+    /// Entering and leaving are checked whenever the cursor moves and on every
+    /// tick, so a region that moves under a resting cursor is caught as well.
+    /// The cursor leaving the window or the window losing focus leaves the
+    /// region too, see `cursor_in_window`.
+    pub fn region(&self, rect: &Signal<Rect>) -> Region {
+        let cursor = self.context().map(|context| context.cursor.position);
+        let moves = self.timed_cursor_moves().map(|moved| moved.value);
+        self.region_at(rect, &cursor, &moves)
+    }
+
+    /// Hit-test `rect` against a cursor signal and the stream of its moves.
+    fn region_at(&self, rect: &Signal<Rect>, cursor: &Signal<(f64, f64)>,
+                 moves: &Stream<(f64, f64)>) -> Region
+    {
+        let in_window = self.cursor_in_window();
+        let hovered = lift3(|rect: Rect, cursor, in_window| in_window && rect.contains(cursor),
+                            rect, cursor, &in_window);
+        let points = moves.merge(&cursor.snapshot(&self.ticks(), |cursor, _| cursor));
+        let exits = self.hover_sink.stream().filter(|&inside| !inside);
+        let area = lift2(|rect: Rect, in_window| (rect, in_window), rect, &in_window);
+        let inside = area
+            .snapshot(&points, |(rect, in_window), point|
+                (in_window && rect.contains(point), point))
+            .merge(&cursor.snapshot(&exits, |cursor, _| (false, cursor)));
+        // Snapshots see the state from before the point is folded in
+        let was_inside = inside.map(|(inside, _)| inside).hold(false);
+        let enter = was_inside
+            .snapshot(&inside, |was, (is, point)| (!was && is, point))
+            .filter_map(|(entered, point)| if entered { Some(point) } else { None });
+        let leave = was_inside
+            .snapshot(&inside, |was, (is, point)| (was && !is, point))
+            .filter_map(|(left, point)| if left { Some(point) } else { None });
+        let clicks = hovered
+            .snapshot(&self.events(), |hovered, event| match event {
+                Event::Click(button, count) if hovered => Some((button, count)),
+                _ => None
+            })
+            .filter_some();
+        Region { hovered: hovered, enter: enter, leave: leave, clicks: clicks }
+    }
+
+    /// Pointer events of a custom title bar, see `TitleBar`.
+    ///
+    /// Left presses on the bar, but not on its buttons, move the window with
+    /// the cursor as with `begin_window_drag`. Decorations can only be turned
+    /// off when building the window, with `WindowBuilder::with_decorations`.
+    ///
+    /// The layout is in window coordinates, since the cursor is hit-tested
+    /// before middleware such as `Letterbox` maps it into a viewport.
+    pub fn title_bar(&mut self, layout: TitleBarLayout) -> TitleBar {
+        let cursor = self.window_cursor();
+        let moves = self.window_cursor_sink.stream();
+        let close = self.region_at(&layout.close, &cursor, &moves);
+        let minimize = self.region_at(&layout.minimize, &cursor, &moves);
+        let maximize = self.region_at(&layout.maximize, &cursor, &moves);
+        let on_button = lift3(|a, b, c| a || b || c,
+                              &close.hovered, &minimize.hovered, &maximize.hovered);
+        let on_bar = self.region_at(&layout.bar, &cursor, &moves).hovered;
+        let draggable = lift2(|bar, button| bar && !button, &on_bar, &on_button);
+        let drags = draggable
+            .snapshot(&self.events(), |draggable, event| match event {
+                Event::Press(Button::Mouse(glutin::MouseButton::Left)) if draggable =>
+                    Some(()),
+                _ => None
+            })
+            .filter_some();
+        let drag_count = drags.fold(0, |count, ()| count + 1);
+        self.window_drag_requests =
+            lift2(|a, b| a + b, &self.window_drag_requests, &drag_count);
+        TitleBar { close: close, minimize: minimize, maximize: maximize, drags: drags }
+    }
+
+    /// Cursor motion, mouse buttons and the wheel, each with the cursor
+    /// position it happened at.
+    pub fn pointer_events(&self) -> Stream<PointerEvent> {
+        use router::PointerAction as Action;
+        let cursor = self.context().map(|context| context.cursor.position);
+        let moves = self.timed_cursor_moves()
+            .map(|moved| PointerEvent::new(moved.value, Action::Move));
+        let buttons = cursor
+            .snapshot(&self.events(), |position, event| match event {
+                Event::Press(Button::Mouse(button)) =>
+                    Some(PointerEvent::new(position, Action::Press(button))),
+                Event::Release(Button::Mouse(button)) =>
+                    Some(PointerEvent::new(position, Action::Release(button))),
+                Event::Click(button, count) =>
+                    Some(PointerEvent::new(position, Action::Click(button, count))),
+                _ => None
+            })
+            .filter_some();
+        let wheel = cursor.snapshot(&self.wheel(), |position, delta|
+            PointerEvent::new(position, Action::Wheel(delta)));
+        moves.merge(&buttons).merge(&wheel)
+    }
+
+    /// Add a gesture recognizer next to the built-in tap and drag ones.
+    ///
+    /// Recognizers with a higher priority are asked first. Once one of them
+    /// recognizes a gesture, the others are reset (see `GestureRegistry`).
+    #[cfg(feature = "gestures")]
+    pub fn add_gesture<R>(&mut self, priority: i32, recognizer: R)
+        where R: GestureRecognizer + 'static
+    {
+        self.gestures.add(priority, recognizer);
+    }
+
+    #[cfg(feature = "gestures")]
+    pub fn gestures(&self) -> Stream<Gesture> {
+        self.gesture_sink.stream().map(|timed| timed.value)
+    }
+
+    /// Gestures with the dispatch time and sequence number of the event that
+    /// completed them.
+    #[cfg(feature = "gestures")]
+    pub fn timed_gestures(&self) -> Stream<Timed<Gesture>> {
+        self.gesture_sink.stream()
+    }
+
+    /// Append middleware that rewrites or drops backend events before
+    /// anything is derived from them.
+    ///
+    /// Middleware runs in the order it was added. Closing the window still
+    /// ends the run loop when the `Closed` event is dropped.
+    pub fn add_middleware<M: Middleware + 'static>(&mut self, middleware: M) {
+        self.middleware.push(Box::new(middleware));
+    }
+
+    /// Emulate key repeat with the given timing, or turn emulation off.
+    ///
+    /// While on, repeats the backend sends as further presses are dropped,
+    /// along with the text they type, and the most recently pressed key
+    /// instead sends `Event::Repeat` followed by the text of its press on
+    /// the ticks at which it is due, the same on every platform.
+    pub fn set_key_repeat(&mut self, repeat: Option<KeyRepeat>) {
+        self.key_repeat = repeat.map(|repeat| (repeat, Repeater::default()));
+    }
+
+    /// Compose dead keys with the following letter in received text, e.g.
+    /// `´` and `e` to `é`.
+    ///
+    /// Only turn this on for backends that deliver dead keys as characters
+    /// of their own; most compose them already. Such a backend sends the
+    /// dead key's press without a character and the accent only along with
+    /// the next character. Only accents received that way are composed, and
+    /// one that is still pending at the end of a poll is passed on as it is.
+    pub fn set_dead_key_composition(&mut self, enabled: bool) {
+        self.composer = if enabled { Some(Composer::default()) } else { None };
+    }
+
+    /// Clamp reported cursor positions to the window bounds.
+    ///
+    /// Some backends report negative or out-of-range positions during fast
+    /// motion at the edges, or while a button is held outside. Off by default;
+    /// see `cursor_clamped` for a clamped signal without changing what
+    /// everything else sees.
+    pub fn set_clamp_cursor(&mut self, enabled: bool) {
+        self.clamp_cursor = enabled;
+    }
+
+    /// Coalesce runs of events that `policy` allows to drop within each
+    /// polled batch, such as many cursor motions between two ticks.
+    ///
+    /// Off by default. Critical events, like buttons and text, are never
+    /// coalesced or dropped. Coalescing happens before the middleware runs.
+    pub fn set_drop_policy(&mut self, policy: Option<DropPolicy>) {
+        self.drop_policy = policy;
+    }
+
+    /// Install a filter that suppresses every backend event it returns
+    /// `false` for. Filters are middleware and run in the same chain.
+    ///
+    /// The release of a key or button whose press got through always gets
+    /// through as well, so that nothing stays held downstream.
+    pub fn add_event_filter<F>(&mut self, mut filter: F)
+        where F: FnMut(&glutin::Event) -> bool + 'static
+    {
+        let mut held: HashSet<Button> = HashSet::new();
+        self.add_middleware(move |event| {
+            let pass = match button_event(&event) {
+                Some(ButtonEvent { button, state: ButtonState::Released }) =>
+                    held.remove(&button) || filter(&event),
+                Some(ButtonEvent { button, state: ButtonState::Pressed }) => {
+                    let pass = filter(&event);
+                    if pass {
+                        held.insert(button);
+                    }
+                    pass
+                },
+                None => filter(&event)
+            };
+            if pass { Some(event) } else { None }
+        });
+    }
+
+    /// Suppress all keyboard, mouse and touch input while `active` is true,
+    /// for example behind a modal overlay.
+    ///
+    /// Keys and buttons held when suppression starts are still released.
+    pub fn suppress_input_while(&mut self, active: Signal<bool>) {
+        self.add_event_filter(move |event| !(is_input(event) && active.sample()));
+    }
+
+    /// A sink for application events that are dispatched along with the
+    /// window events, see `CustomSink`.
+    pub fn custom_sink<T: Clone + Send + Sync + 'static>(&mut self) -> CustomSink<T> {
+        let sink = CustomSink::new();
+        self.custom_sinks.push(Box::new(sink.clone()));
+        sink
+    }
+
+    /// A layer stack for this window's events.
+    pub fn layers(&self) -> Layers {
+        Layers::new(&self.timed_events())
+    }
+
+    /// A router for this window's pointer events.
+    pub fn router(&self) -> Router {
+        Router::new(&self.pointer_events())
+    }
+
+    /// A focus manager routing this window's keyboard events.
+    pub fn focus_manager(&self) -> FocusManager {
+        FocusManager::new(&self.events(), &self.modifiers())
+    }
+
+    /// Previous and current window size.
+    ///
+    /// The pair only moves on when the size actually changes, so the previous
+    /// value is the one before the latest change. Both start out as the
+    /// initial value, which makes the first delta zero.
+    pub fn size_pair(&self) -> Signal<((u32, u32), (u32, u32))> {
+        self.with_previous(|context| context.window.size)
+    }
+
+    /// Previous and current cursor position, see `size_pair`.
+    pub fn cursor_pair(&self) -> Signal<((f64, f64), (f64, f64))> {
+        self.with_previous(|context| context.cursor.position)
+    }
+
+    /// Previous and current focus state, see `size_pair`.
+    pub fn focus_pair(&self) -> Signal<(bool, bool)> {
+        self.with_previous(|context| context.window.focus)
+    }
+
+    fn with_previous<T, F>(&self, project: F) -> Signal<(T, T)>
+        where T: Clone + PartialEq + Send + Sync + 'static,
+              F: Fn(&Context) -> T + Send + Sync + 'static
+    {
+        let context = self.initial_context();
+        let initial = project(&context);
+        self.update_sink.stream()
+            .fold(
+                (context, (initial.clone(), initial)),
+                move |(context, (previous, current)), update| {
+                    let context = update.apply(context);
+                    let value = project(&context);
+                    if value == current {
+                        (context, (previous, current))
+                    } else {
+                        (context, (current, value))
+                    }
+                })
+            .map(|(_, pair)| pair)
     }
 
     pub fn events(&self) -> Stream<Event> {
+        self.timed_events().map(|event| event.value)
+    }
+
+    pub fn timed_events(&self) -> Stream<Timed<Event>> {
         self.event_sink.stream()
     }
+
+    /// Events, cursor motion, wheel deltas, resizes and touches in the order
+    /// they were dispatched.
+    ///
+    /// Custom events and touchpad pressure are not included.
+    pub fn dispatched(&self) -> Stream<Timed<Dispatched>> {
+        let dispatched = self.timed_events().map(retag(Dispatched::Event))
+            .merge(&self.timed_cursor_moves().map(retag(Dispatched::Motion)))
+            .merge(&self.timed_wheel().map(retag(Dispatched::Wheel)))
+            .merge(&self.timed_resizes().map(retag(Dispatched::Resize)));
+        #[cfg(feature = "touch")]
+        let dispatched = dispatched
+            .merge(&self.timed_touches().map(retag(Dispatched::Touch)));
+        dispatched
+    }
+
+    /// A running hash of everything dispatched, see `sequence_hash`.
+    ///
+    /// Compare the final hash of a replayed run with that of the recorded
+    /// one to check that it saw exactly the same sequence.
+    pub fn sequence_hash(&self, include_time: bool) -> Signal<u64> {
+        replay::sequence_hash(&self.dispatched(), include_time)
+    }
+
+    /// Presses and releases of keys and mouse buttons.
+    pub fn button_events(&self) -> Stream<ButtonEvent> {
+        self.timed_button_events().map(|event| event.value)
+    }
+
+    pub fn timed_button_events(&self) -> Stream<Timed<ButtonEvent>> {
+        self.button_sink.stream()
+    }
+
+    /// Received text as shared strings.
+    ///
+    /// The same text as `Event::Text`, but cloning it for every listener only
+    /// bumps a reference count instead of copying the string.
+    pub fn text(&self) -> Stream<Arc<str>> {
+        self.timed_text().map(|text| text.value)
+    }
+
+    pub fn timed_text(&self) -> Stream<Timed<Arc<str>>> {
+        self.text_sink.stream()
+    }
+
+    /// Received text split into grapheme clusters, so that an emoji sequence
+    /// or a letter with combining marks arrives as one value.
+    ///
+    /// The backend sends text one character at a time, so a cluster is only
+    /// sent once the next character starts a new one or anything but text is
+    /// dispatched, even if that happens in a later poll.
+    #[cfg(feature = "unicode-segmentation")]
+    pub fn graphemes(&self) -> Stream<Arc<str>> {
+        self.timed_graphemes().map(|grapheme| grapheme.value)
+    }
+
+    /// Grapheme clusters with the dispatch time and sequence number of their
+    /// first character.
+    #[cfg(feature = "unicode-segmentation")]
+    pub fn timed_graphemes(&self) -> Stream<Timed<Arc<str>>> {
+        self.grapheme_sink.stream()
+    }
+
+    /// Messages of panics caught in the render closure.
+    pub fn render_panics(&self) -> Stream<String> {
+        self.panic_sink.stream()
+    }
+
+    /// Ticks of `run_with` that exceeded the time budget of one frame.
+    pub fn late_frames(&self) -> Stream<LateFrame> {
+        self.late_sink.stream()
+    }
+
+    /// Event counts and frame timings of the last complete second.
+    ///
+    /// This is updated once per second by the run loop (or `step`). Frame
+    /// timings are only recorded by the run loop.
+    pub fn metrics(&self) -> Signal<Metrics> {
+        self.metrics_sink.stream().hold(Metrics::default())
+    }
+
+    /// Latency of the values on `stream` since their dispatch, over the last
+    /// `window` values, measured by the driver's clock. See `latency_probe`.
+    pub fn latency_probe<T>(&self, stream: &Stream<Timed<T>>, window: usize)
+        -> Signal<LatencyStats>
+        where T: Clone + Send + Sync + 'static
+    {
+        let clock = self.clock.clone();
+        latency::latency_probe(stream, window, move || clock())
+    }
+
+    /// Individual wheel deltas in their original unit.
+    pub fn wheel(&self) -> Stream<ScrollDelta> {
+        self.timed_wheel().map(|delta| delta.value)
+    }
+
+    pub fn timed_wheel(&self) -> Stream<Timed<ScrollDelta>> {
+        self.wheel_sink.stream()
+    }
+
+    #[cfg(feature = "touch")]
+    pub fn touches(&self) -> Stream<Touch> {
+        self.timed_touches().map(|touch| touch.value)
+    }
+
+    #[cfg(feature = "touch")]
+    pub fn timed_touches(&self) -> Stream<Timed<Touch>> {
+        self.touch_sink.stream()
+    }
+
+    /// Touchpad force between 0 and 1, on force-sensing touchpads only.
+    pub fn touchpad_pressure(&self) -> Stream<f64> {
+        self.pressure_sink.stream()
+    }
+
+    /// Accumulated horizontal wheel position in pixels.
+    pub fn wheel_x(&self) -> Signal<f64> {
+        self.context().map(|context| context.cursor.wheel.0)
+    }
+
+    /// Accumulated vertical wheel position in pixels.
+    pub fn wheel_y(&self) -> Signal<f64> {
+        self.context().map(|context| context.cursor.wheel.1)
+    }
+
+    /// Wheel deltas in pixels, converted with the line height at the time
+    /// they were dispatched.
+    fn wheel_pixels(&self) -> Stream<(f64, f64)> {
+        self.update_sink.stream()
+            .filter_map(|update| match update {
+                Update::Cursor(CursorUpdate::WheelDelta(dx, dy)) => Some((dx, dy)),
+                _ => None
+            })
+    }
+
+    /// Non-zero horizontal wheel deltas in pixels.
+    pub fn wheel_x_deltas(&self) -> Stream<f64> {
+        self.wheel_pixels()
+            .map(|(dx, _)| dx)
+            .filter(|&dx| dx != 0.0)
+    }
+
+    /// Non-zero vertical wheel deltas in pixels.
+    pub fn wheel_y_deltas(&self) -> Stream<f64> {
+        self.wheel_pixels()
+            .map(|(_, dy)| dy)
+            .filter(|&dy| dy != 0.0)
+    }
+
+    /// Time of each tick of the run loop in nanoseconds, sent after the
+    /// tick's events have been dispatched and before rendering.
+    pub fn ticks(&self) -> Stream<u64> {
+        self.tick_sink.stream()
+    }
+
+    /// Wheel position with kinetic smoothing.
+    ///
+    /// Each wheel delta is spread out over time, with the remaining motion
+    /// decaying exponentially at the rate `decay` (per second). Larger values
+    /// make scrolling snappier.
+    pub fn smooth_wheel(&self, decay: f64) -> Signal<(f64, f64)> {
+        assert!(decay > 0.0);
+        let impulses = self.wheel_pixels()
+            .map(|(dx, dy)| MomentumUpdate::Impulse(dx, dy));
+        let advances = self.ticks().map(MomentumUpdate::Advance);
+        impulses.merge(&advances)
+            .fold(Momentum::default(), move |old, update| update.apply(old, decay))
+            .map(|momentum| momentum.position)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use glutin::ElementState::Pressed;
+    use glutin::Event::{KeyboardInput, MouseMoved, MouseWheel, ReceivedCharacter, Resized};
+    use glutin::MouseScrollDelta::{LineDelta, PixelDelta};
+    use glutin::TouchPhase::Moved;
+    use glutin::VirtualKeyCode::LShift;
+    use ::{Event, ScrollDelta};
+    use testing::{collect, snapshot, MockWindow};
+    use super::{horizontal_scroll, WindowDriver};
+
+    #[test]
+    fn shuts_down_once_when_step_reports_closing() {
+        use std::cell::Cell;
+        use std::rc::Rc;
+        let window = MockWindow::shared();
+        let mut driver = WindowDriver::new(window.clone());
+        let hooks = Rc::new(Cell::new(0));
+        let hook_count = hooks.clone();
+        driver.on_shutdown(move |_| hook_count.set(hook_count.get() + 1));
+        let shutdowns = driver.events()
+            .filter(|event| *event == Event::Shutdown)
+            .fold(0, |n, _| n + 1);
+        assert!(driver.step(0));
+        assert_eq!((shutdowns.sample(), hooks.get()), (0, 0));
+        window.borrow_mut().set_should_close(true);
+        assert!(!driver.step(1));
+        assert!(!driver.step(2));
+        assert_eq!((shutdowns.sample(), hooks.get()), (1, 1));
+    }
+
+    #[test]
+    fn releases_modifiers_when_focus_is_lost() {
+        use glutin::Event::Focused;
+        let shift = snapshot(vec![
+            (0, vec![KeyboardInput(Pressed, 0, Some(LShift))]),
+            (1, vec![Focused(false)])
+        ], |driver| driver.modifiers().map(|modifiers| modifiers.shift()));
+        assert_eq!(shift, vec![true, false]);
+    }
+
+    #[test]
+    fn held_lock_keys_toggle_once() {
+        use glutin::ElementState::Released;
+        use glutin::VirtualKeyCode::Capital;
+        let caps = snapshot(vec![
+            (0, vec![KeyboardInput(Pressed, 0, Some(Capital))]),
+            (1, vec![KeyboardInput(Pressed, 0, Some(Capital)),
+                     KeyboardInput(Pressed, 0, Some(Capital))]),
+            (2, vec![KeyboardInput(Released, 0, Some(Capital)),
+                     KeyboardInput(Pressed, 0, Some(Capital))])
+        ], |driver| driver.lock_keys().map(|locks| locks.caps));
+        assert_eq!(caps, vec![true, true, false]);
+    }
+
+    #[test]
+    fn turns_vertical_scrolls_horizontal() {
+        assert_eq!(horizontal_scroll(ScrollDelta::Lines(0.0, 2.0)),
+                   ScrollDelta::Lines(2.0, 0.0));
+        assert_eq!(horizontal_scroll(ScrollDelta::Pixels(0.0, -5.0)),
+                   ScrollDelta::Pixels(-5.0, 0.0));
+        assert_eq!(horizontal_scroll(ScrollDelta::Lines(1.0, 2.0)),
+                   ScrollDelta::Lines(1.0, 2.0));
+    }
+
+    #[test]
+    fn wheel_deltas_use_the_line_height_at_dispatch() {
+        let window = MockWindow::shared();
+        let mut driver = WindowDriver::new(window.clone());
+        let x = collect(&driver.wheel_x_deltas());
+        let y = collect(&driver.wheel_y_deltas());
+        driver.set_shift_scrolls_horizontally(true);
+        driver.set_line_height(10.0);
+        window.borrow_mut().push(MouseWheel(LineDelta(0.0, 1.0), Moved));
+        driver.step(0);
+        driver.set_line_height(20.0);
+        window.borrow_mut().push(KeyboardInput(Pressed, 0, Some(LShift)));
+        window.borrow_mut().push(MouseWheel(LineDelta(0.0, 1.0), Moved));
+        driver.step(1);
+        assert_eq!((x.sample(), y.sample()), (vec![20.0], vec![10.0]));
+    }
+
+
+    #[test]
+    fn pairs_keep_the_value_before_the_latest_change() {
+        let pairs = snapshot(
+            vec![
+                (0, vec![]),
+                (10, vec![MouseMoved(1, 2)]),
+                (20, vec![MouseWheel(PixelDelta(0.0, 1.0), Moved)]),
+                (30, vec![MouseMoved(5, 2)])
+            ],
+            |driver| driver.cursor_pair()
+        );
+        assert_eq!(pairs, vec![
+            ((0.0, 0.0), (0.0, 0.0)),
+            ((0.0, 0.0), (1.0, 2.0)),
+            ((0.0, 0.0), (1.0, 2.0)),
+            ((1.0, 2.0), (5.0, 2.0))
+        ]);
+    }
+
+    #[test]
+    fn drops_repeated_resizes() {
+        let sizes = snapshot(
+            vec![
+                (0, vec![Resized(10, 20)]),
+                (10, vec![Resized(10, 20), Resized(10, 20)]),
+                (20, vec![Resized(30, 20)])
+            ],
+            |driver| collect(&driver.resizes())
+        );
+        assert_eq!(sizes.last().unwrap(), &vec![(10, 20), (30, 20)]);
+    }
+
+    #[test]
+    fn settles_resizes_after_a_quiet_period() {
+        let settled = snapshot(
+            vec![
+                (0, vec![Resized(10, 20)]),
+                (40, vec![Resized(15, 20)]),
+                (90, vec![]),
+                (140, vec![]),
+                (200, vec![])
+            ],
+            |driver| collect(&driver.resize_settled(Duration::new(0, 100)))
+        );
+        assert_eq!(settled[3], vec![(15, 20)]);
+        assert_eq!(settled[4], vec![(15, 20)]);
+        assert!(settled[2].is_empty());
+    }
+
+    #[test]
+    fn delays_values_by_tick_time() {
+        let window = MockWindow::shared();
+        let mut driver = WindowDriver::new(window.clone());
+        driver.set_clock(|| 0);
+        let text = driver.events();
+        let delayed = collect(&driver.delay(&text, Duration::new(0, 100)));
+        let timeout = driver.after(Duration::new(0, 150)).hold(0);
+        driver.step(0);
+        window.borrow_mut().push(ReceivedCharacter('a'));
+        driver.step(50);
+        assert!(delayed.sample().is_empty());
+        driver.step(99);
+        assert!(delayed.sample().is_empty());
+        driver.step(100);
+        assert_eq!(delayed.sample(), vec![Event::Text("a".to_string())]);
+        assert_eq!(timeout.sample(), 0);
+        driver.step(160);
+        assert_eq!(timeout.sample(), 160);
+    }
+
+    #[test]
+    fn delays_values_from_their_arrival_between_ticks() {
+        use carboxyl::Sink;
+        use testing::MockClock;
+        let clock = MockClock::new(1_000);
+        let mut driver = WindowDriver::new(MockWindow::shared());
+        let driver_clock = clock.clone();
+        driver.set_clock(move || driver_clock.now());
+        let sink = Sink::new();
+        let delayed = collect(&driver.delay(&sink.stream(), Duration::new(0, 100)));
+        sink.send('a');
+        clock.set(1_050);
+        driver.step(1_050);
+        assert!(delayed.sample().is_empty());
+        clock.set(1_080);
+        sink.send('b');
+        clock.set(1_100);
+        driver.step(1_100);
+        assert_eq!(delayed.sample(), vec!['a']);
+        clock.set(1_170);
+        driver.step(1_170);
+        assert_eq!(delayed.sample(), vec!['a']);
+        clock.set(1_180);
+        driver.step(1_180);
+        assert_eq!(delayed.sample(), vec!['a', 'b']);
+    }
+
+    #[test]
+    fn fires_every_period_independent_of_ticks() {
+        let periods = snapshot(
+            vec![(0, vec![]), (25, vec![]), (30, vec![]), (65, vec![])],
+            |driver| collect(&driver.every(Duration::new(0, 10)))
+        );
+        assert_eq!(periods[1], vec![10, 20]);
+        assert_eq!(periods[2], vec![10, 20, 30]);
+        assert_eq!(periods[3], vec![10, 20, 30, 40, 50, 60]);
+    }
+
+    #[test]
+    fn held_duration_resets_when_focus_is_lost() {
+        use glutin::Event::Focused;
+        use glutin::VirtualKeyCode::Space;
+        use ::Button;
+        let held = snapshot(vec![
+            (0, vec![KeyboardInput(Pressed, 0, Some(Space))]),
+            (10, vec![]),
+            (20, vec![Focused(false)]),
+            (30, vec![Focused(true)])
+        ], |driver| driver.held_duration(Button::Keyboard(Space)));
+        assert_eq!(held, vec![0, 10, 0, 0]);
+    }
+
+    #[test]
+    fn keeps_the_latest_cursor_positions() {
+        let history = snapshot(
+            vec![
+                (0, vec![MouseMoved(1, 1), MouseMoved(2, 2)]),
+                (10, vec![MouseMoved(3, 3)])
+            ],
+            |driver| driver.cursor_history(2)
+        );
+        let last: Vec<_> = history[1].iter()
+            .map(|timed| (timed.time, timed.value))
+            .collect();
+        assert_eq!(last, vec![(0, (2.0, 2.0)), (10, (3.0, 3.0))]);
+    }
+
+    #[test]
+    fn reports_entering_leaving_and_clicking_a_region() {
+        use glutin::Event::MouseInput;
+        use glutin::MouseButton::Left;
+        use carboxyl::Signal;
+        use region::Rect;
+        let counts = snapshot(
+            vec![
+                (0, vec![MouseMoved(0, 0)]),
+                (10, vec![MouseMoved(15, 15), MouseInput(Pressed, Left)]),
+                (20, vec![MouseMoved(40, 15), MouseInput(Pressed, Left)])
+            ],
+            |driver| {
+                let rect = Signal::new(Rect::new(10.0, 10.0, 20.0, 20.0));
+                let region = driver.region(&rect);
+                let enters = region.enter.fold(0, |n, _| n + 1);
+                let leaves = region.leave.fold(0, |n, _| n + 1);
+                let clicks = region.clicks.fold(0, |n, _| n + 1);
+                carboxyl::lift::lift3(|a, b, c| (a, b, c), &enters, &leaves, &clicks)
+            }
+        );
+        assert_eq!(counts, vec![(0, 0, 0), (1, 0, 1), (1, 1, 1)]);
+    }
+
+    #[test]
+    fn leaves_a_region_when_the_cursor_leaves_the_window() {
+        use carboxyl::Signal;
+        use glutin::Event::{Focused, MouseLeft};
+        use region::Rect;
+        let states = snapshot(
+            vec![
+                (0, vec![MouseMoved(2, 5)]),
+                (10, vec![MouseLeft]),
+                (20, vec![MouseMoved(3, 5)]),
+                (30, vec![Focused(false)])
+            ],
+            |driver| {
+                let rect = Signal::new(Rect::new(0.0, 0.0, 10.0, 10.0));
+                let region = driver.region(&rect);
+                let enters = region.enter.fold(0, |n, _| n + 1);
+                let leaves = region.leave.fold(0, |n, _| n + 1);
+                carboxyl::lift::lift3(|a, b, c| (a, b, c), &region.hovered, &enters, &leaves)
+            }
+        );
+        assert_eq!(states, vec![(true, 1, 0), (false, 1, 1), (true, 2, 1), (false, 2, 2)]);
+    }
+
+    #[test]
+    fn dispatches_custom_events_before_window_events_of_a_tick() {
+        let window = MockWindow::shared();
+        let mut driver = WindowDriver::new(window.clone());
+        driver.set_clock(|| 0);
+        let messages = driver.custom_sink::<&'static str>();
+        let message_seqs = messages.timed_stream().map(|timed| timed.seq).hold(0);
+        let event_seqs = driver.timed_events().map(|timed| timed.seq).hold(0);
+        messages.send("loaded");
+        window.borrow_mut().push(ReceivedCharacter('a'));
+        driver.step(0);
+        assert_eq!((message_seqs.sample(), event_seqs.sample()), (1, 2));
+    }
+
+    #[test]
+    fn filters_suppress_input_before_dispatch() {
+        use carboxyl::Sink;
+        let modal = Sink::new();
+        let window = MockWindow::shared();
+        let mut driver = WindowDriver::new(window.clone());
+        driver.suppress_input_while(modal.stream().hold(false));
+        let text = driver.events().fold(0, |n, _| n + 1);
+        let context = driver.context();
+        modal.send(true);
+        window.borrow_mut().push(ReceivedCharacter('a'));
+        window.borrow_mut().push(Resized(5, 5));
+        driver.step(0);
+        modal.send(false);
+        window.borrow_mut().push(ReceivedCharacter('b'));
+        driver.step(1);
+        assert_eq!(text.sample(), 1);
+        assert_eq!(context.sample().window.size, (5, 5));
+    }
+
+    #[test]
+    fn filters_let_releases_of_earlier_presses_through() {
+        use carboxyl::Sink;
+        use glutin::ElementState::{Pressed, Released};
+        use glutin::VirtualKeyCode::{A, B};
+        use ::Button;
+        let modal = Sink::new();
+        let window = MockWindow::shared();
+        let mut driver = WindowDriver::new(window.clone());
+        driver.suppress_input_while(modal.stream().hold(false));
+        let events = collect(&driver.events());
+        window.borrow_mut().push(KeyboardInput(Pressed, 0, Some(A)));
+        driver.step(0);
+        modal.send(true);
+        for event in [KeyboardInput(Pressed, 0, Some(B)), KeyboardInput(Released, 0, Some(A)),
+                      KeyboardInput(Released, 0, Some(B))] {
+            window.borrow_mut().push(event);
+        }
+        driver.step(1);
+        assert_eq!(events.sample(), vec![
+            Event::Press(Button::Keyboard(A)),
+            Event::Release(Button::Keyboard(A))
+        ]);
+    }
+
+    #[test]
+    fn drop_policy_coalesces_motion_within_a_batch() {
+        let window = MockWindow::shared();
+        let mut driver = WindowDriver::new(window.clone());
+        driver.set_drop_policy(Some(::DropPolicy::default()));
+        let moves = driver.timed_cursor_moves().fold(0, |n, _| n + 1);
+        let text = driver.events().fold(0, |n, _| n + 1);
+        let context = driver.context();
+        for event in [MouseMoved(1, 1), MouseMoved(2, 2), ReceivedCharacter('a'),
+                          ReceivedCharacter('b'), MouseMoved(3, 3)] {
+            window.borrow_mut().push(event);
+        }
+        driver.step(0);
+        assert_eq!((moves.sample(), text.sample()), (2, 2));
+        assert_eq!(context.sample().cursor.position, (3.0, 3.0));
+    }
+
+    #[test]
+    fn button_events_mirror_presses_and_releases() {
+        use glutin::ElementState::{Pressed, Released};
+        use glutin::Event::MouseInput;
+        use glutin::{MouseButton, VirtualKeyCode};
+        use ::{Button, ButtonEvent, ButtonState};
+        let buttons = snapshot(vec![
+            (0, vec![KeyboardInput(Pressed, 0, Some(VirtualKeyCode::A))]),
+            (1, vec![ReceivedCharacter('a'), MouseInput(Released, MouseButton::Left)])
+        ], |driver| driver.button_events()
+            .map(|event: ButtonEvent| Some((event.button, event.state)))
+            .hold(None));
+        assert_eq!(buttons, vec![
+            Some((Button::Keyboard(VirtualKeyCode::A), ButtonState::Pressed)),
+            Some((Button::Mouse(MouseButton::Left), ButtonState::Released))
+        ]);
+    }
+
+    #[test]
+    fn text_is_shared_between_listeners() {
+        use std::sync::Arc;
+        let window = MockWindow::shared();
+        let mut driver = WindowDriver::new(window.clone());
+        let first = driver.text().hold(Arc::from(""));
+        let second = driver.text().hold(Arc::from(""));
+        window.borrow_mut().push(ReceivedCharacter('ä'));
+        driver.step(0);
+        assert_eq!(&*first.sample(), "ä");
+        assert!(Arc::ptr_eq(&first.sample(), &second.sample()));
+    }
+
+    #[test]
+    fn aspect_ratio_is_finite_before_the_first_resize() {
+        let ratios = snapshot(vec![
+            (0, vec![]),
+            (1, vec![Resized(200, 100)]),
+            (2, vec![Resized(200, 0)])
+        ], |driver| driver.aspect_ratio());
+        assert_eq!(ratios, vec![1.0, 2.0, 1.0]);
+    }
+
+    #[test]
+    fn letterbox_middleware_follows_resizes() {
+        use ::Letterbox;
+        let window = MockWindow::shared();
+        let mut driver = WindowDriver::new(window.clone());
+        let viewport = driver.letterbox(1.0);
+        driver.add_middleware(Letterbox::new(viewport));
+        let context = driver.context();
+        window.borrow_mut().push(Resized(300, 100));
+        window.borrow_mut().push(MouseMoved(150, 50));
+        driver.step(0);
+        assert_eq!(context.sample().cursor.position, (50.0, 50.0));
+    }
+
+    #[test]
+    fn window_chrome_ignores_the_letterbox() {
+        use ::Letterbox;
+        let window = MockWindow::shared();
+        let mut driver = WindowDriver::new(window.clone());
+        let viewport = driver.letterbox(1.0);
+        driver.add_middleware(Letterbox::new(viewport));
+        let context = driver.context();
+        let edge = driver.resize_edge(4.0);
+        let inside = driver.cursor_in_window();
+        window.borrow_mut().push(Resized(300, 100));
+        window.borrow_mut().push(MouseMoved(2, 50));
+        driver.step(0);
+        assert_eq!(context.sample().cursor.position, (-98.0, 50.0));
+        assert_eq!((edge.sample(), inside.sample()), (Some(::ResizeEdge::West), true));
+    }
+
+    #[test]
+    fn cursor_in_window_follows_enter_leave_and_focus() {
+        use glutin::Event::{Focused, MouseEntered, MouseLeft};
+        let inside = snapshot(vec![
+            (0, vec![MouseEntered]),
+            (1, vec![MouseLeft]),
+            (2, vec![MouseMoved(5, 5)]),
+            (3, vec![Focused(false)]),
+            (4, vec![Resized(10, 10), MouseMoved(20, 5)])
+        ], |driver| driver.cursor_in_window());
+        assert_eq!(inside, vec![true, false, true, false, false]);
+    }
+
+    #[test]
+    fn clamps_the_cursor_to_the_window() {
+        let script = || vec![(0, vec![Resized(10, 20), MouseMoved(-3, 25)])];
+        let clamped = snapshot(script(), |driver| driver.cursor_clamped());
+        assert_eq!(clamped, vec![(0.0, 19.0)]);
+        let reported = snapshot(script(), |driver| {
+            driver.set_clamp_cursor(true);
+            driver.context().map(|context| context.cursor.position)
+        });
+        assert_eq!(reported, vec![(0.0, 19.0)]);
+    }
+
+    #[test]
+    fn starts_out_with_the_reported_window_size() {
+        use testing::ScriptSource;
+        let mut source = ScriptSource::new().with_size(200, 100);
+        source.push(vec![MouseMoved(300, 50)]);
+        let mut driver = WindowDriver::new(source);
+        let size = driver.size();
+        let aspect = driver.aspect_ratio();
+        let viewport = driver.letterbox(1.0);
+        let clamped = driver.cursor_clamped();
+        driver.step(0);
+        assert_eq!((size.sample(), aspect.sample()), ((200, 100), 2.0));
+        assert_eq!(viewport.sample(), ::Rect::new(50.0, 0.0, 100.0, 100.0));
+        assert_eq!(clamped.sample(), (199.0, 50.0));
+    }
+
+    #[test]
+    fn resizes_the_window_from_a_dragged_edge() {
+        use glutin::Event::MouseInput;
+        use glutin::MouseButton::Left;
+        use ::EventSource;
+        let window = MockWindow::shared();
+        window.borrow().set_inner_size(100, 50);
+        let mut driver = WindowDriver::new(window.clone());
+        let edge = driver.resize_drags(4.0).map(Some).hold(None);
+        window.borrow_mut().push(Resized(100, 50));
+        window.borrow_mut().push(MouseMoved(2, 25));
+        window.borrow_mut().push(MouseInput(Pressed, Left));
+        driver.step(0);
+        assert_eq!(edge.sample(), Some(::ResizeEdge::West));
+        driver.begin_window_resize().send(::ResizeEdge::West);
+        window.borrow_mut().push(MouseMoved(-8, 25));
+        driver.step(1);
+        assert_eq!(window.borrow().inner_size(), Some((110, 50)));
+        assert_eq!(window.borrow().position(), Some((-10, 0)));
+    }
+
+    #[test]
+    fn title_bar_drags_the_window_and_reports_button_clicks() {
+        use carboxyl::Signal;
+        use glutin::ElementState::{Pressed, Released};
+        use glutin::Event::MouseInput;
+        use glutin::MouseButton::Left;
+        use ::{EventSource, Rect, TitleBarLayout};
+        let window = MockWindow::shared();
+        let mut driver = WindowDriver::new(window.clone());
+        let bar = driver.title_bar(TitleBarLayout {
+            bar: Signal::new(Rect::new(0.0, 0.0, 100.0, 20.0)),
+            close: Signal::new(Rect::new(80.0, 0.0, 20.0, 20.0)),
+            minimize: Signal::new(Rect::new(40.0, 0.0, 20.0, 20.0)),
+            maximize: Signal::new(Rect::new(60.0, 0.0, 20.0, 20.0))
+        });
+        let closes = bar.close.clicks.fold(0, |n, _| n + 1);
+        for event in [MouseMoved(90, 10), MouseInput(Pressed, Left),
+                      MouseInput(Released, Left), MouseMoved(10, 10),
+                      MouseInput(Pressed, Left)] {
+            window.borrow_mut().push(event);
+        }
+        driver.step(0);
+        window.borrow_mut().push(MouseMoved(15, 12));
+        driver.step(1);
+        assert_eq!(closes.sample(), 1);
+        assert_eq!(window.borrow().position(), Some((5, 2)));
+    }
+
+    #[test]
+    fn emulates_key_repeat_on_ticks() {
+        use glutin::ElementState::{Pressed, Released};
+        use glutin::VirtualKeyCode::A;
+        use ::{Button, KeyRepeat};
+        let a = Button::Keyboard(A);
+        let events = snapshot(vec![
+            (0, vec![KeyboardInput(Pressed, 0, Some(A))]),
+            (100_000_000, vec![KeyboardInput(Pressed, 0, Some(A))]),
+            (500_000_000, vec![]),
+            (700_000_000, vec![KeyboardInput(Released, 0, Some(A))]),
+            (900_000_000, vec![])
+        ], |driver| {
+            let repeat = KeyRepeat::new(Duration::from_millis(500), 10.0);
+            driver.set_key_repeat(Some(repeat));
+            collect(&driver.events())
+        });
+        assert_eq!(events.last().unwrap(), &vec![
+            Event::Press(a),
+            Event::Repeat(a),
+            Event::Release(a)
+        ]);
+    }
+
+    #[test]
+    fn repeats_text_at_the_emulated_rate() {
+        use glutin::VirtualKeyCode::A;
+        use ::KeyRepeat;
+        let typed = || vec![KeyboardInput(Pressed, 0, Some(A)), ReceivedCharacter('a')];
+        let text = snapshot(vec![
+            (0, typed()),
+            (100_000_000, typed()),
+            (200_000_000, typed()),
+            (600_000_000, vec![])
+        ], |driver| {
+            let repeat = KeyRepeat::new(Duration::from_millis(500), 10.0);
+            driver.set_key_repeat(Some(repeat));
+            driver.events().fold(String::new(), |mut text, event| {
+                if let Event::Text(chunk) = event {
+                    text.push_str(&chunk);
+                }
+                text
+            })
+        });
+        assert_eq!(text, vec!["a", "a", "a", "aaa"]);
+    }
+
+    #[test]
+    fn composes_dead_keys_within_a_tick() {
+        use glutin::VirtualKeyCode::{E, Grave};
+        let dead_key = || vec![
+            KeyboardInput(Pressed, 0, Some(Grave)),
+            KeyboardInput(Pressed, 0, Some(E)),
+            ReceivedCharacter('\u{b4}')
+        ];
+        let mut composed = dead_key();
+        composed.push(ReceivedCharacter('e'));
+        let text = snapshot(vec![(0, composed), (1, dead_key())], |driver| {
+            driver.set_dead_key_composition(true);
+            driver.events().fold(String::new(), |mut text, event| {
+                if let Event::Text(chunk) = event {
+                    text.push_str(&chunk);
+                }
+                text
+            })
+        });
+        assert_eq!(text, vec!["é".to_string(), "é\u{b4}".to_string()]);
+    }
+
+    #[test]
+    #[cfg(feature = "unicode-segmentation")]
+    fn segments_text_into_grapheme_clusters_across_polls() {
+        let chars = |text: &str| -> Vec<::glutin::Event> {
+            text.chars().map(ReceivedCharacter).collect()
+        };
+        let graphemes = snapshot(vec![
+            (0, chars("e")),
+            (1, chars("\u{301}\u{1f469}\u{200d}")),
+            (2, chars("\u{1f4bb}x")),
+            (3, vec![MouseMoved(0, 0)])
+        ], |driver| collect(&driver.timed_graphemes()
+            .map(|grapheme| (grapheme.time, grapheme.value.to_string()))));
+        let expected = vec![
+            (0, "e\u{301}".to_string()),
+            (1, "\u{1f469}\u{200d}\u{1f4bb}".to_string()),
+            (2, "x".to_string())
+        ];
+        assert_eq!(graphemes[1], expected[..1].to_vec());
+        assert_eq!(graphemes[2], expected[..2].to_vec());
+        assert_eq!(graphemes[3], expected);
+    }
 }
@@ -0,0 +1,175 @@
+use glutin;
+
+/// Kinds of backend events, as far as dropping them is concerned.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum EventCategory {
+    /// Cursor motion.
+    Motion,
+    Wheel,
+    /// Window resizes and moves.
+    Geometry,
+    /// Touchpad pressure.
+    Pressure,
+    /// Redraw requests and wakeups.
+    Refresh,
+    /// Keys and mouse buttons.
+    Button,
+    Text,
+    Touch,
+    /// Closing, focus, suspension, the cursor entering or leaving and
+    /// dropped files.
+    Window
+}
+
+impl EventCategory {
+    pub fn of(event: &glutin::Event) -> EventCategory {
+        use glutin::Event::*;
+        match *event {
+            MouseMoved(..) => EventCategory::Motion,
+            MouseWheel(..) => EventCategory::Wheel,
+            Resized(..) | Moved(..) => EventCategory::Geometry,
+            TouchpadPressure(..) => EventCategory::Pressure,
+            Refresh | Awakened => EventCategory::Refresh,
+            KeyboardInput(..) | MouseInput(..) => EventCategory::Button,
+            ReceivedCharacter(..) => EventCategory::Text,
+            Touch(..) => EventCategory::Touch,
+            Closed | Focused(..) | Suspended(..) | DroppedFile(..) | MouseEntered |
+            MouseLeft => EventCategory::Window
+        }
+    }
+
+    /// Whether losing an event of this kind breaks state derived from it,
+    /// such as held buttons or typed text. These are never dropped.
+    pub fn is_critical(&self) -> bool {
+        matches!(*self,
+            EventCategory::Button | EventCategory::Text | EventCategory::Touch |
+            EventCategory::Window)
+    }
+
+    /// Whether events of this kind happen at the cursor position, so the
+    /// motion before them must not be lost.
+    pub fn is_positional(&self) -> bool {
+        matches!(*self, EventCategory::Button | EventCategory::Wheel)
+    }
+
+    /// Whether events of this kind may only be merged into one another, not
+    /// dropped. Dropping the last resize or move leaves the window state
+    /// stale, and dropping wheel events loses scroll distance.
+    pub fn is_coalesce_only(&self) -> bool {
+        matches!(*self, EventCategory::Geometry | EventCategory::Wheel)
+    }
+}
+
+/// Which kinds of events may be dropped or coalesced under load.
+///
+/// Critical categories (buttons, text, touch and window events) can not be
+/// allowed, so correctness-critical events are never lost. Geometry and
+/// wheel events are only ever coalesced, never dropped.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DropPolicy {
+    allowed: Vec<EventCategory>
+}
+
+impl Default for DropPolicy {
+    /// Motion, pressure and refresh events may be dropped, geometry and
+    /// wheel events coalesced.
+    fn default() -> DropPolicy {
+        use self::EventCategory::*;
+        DropPolicy { allowed: vec![Motion, Wheel, Geometry, Pressure, Refresh] }
+    }
+}
+
+impl DropPolicy {
+    /// A policy that drops nothing.
+    pub fn none() -> DropPolicy {
+        DropPolicy { allowed: vec![] }
+    }
+
+    /// Also allow dropping `category`, or only coalescing it if it is
+    /// coalesce-only. Critical categories are ignored.
+    pub fn allow(mut self, category: EventCategory) -> DropPolicy {
+        if !category.is_critical() && !self.allowed.contains(&category) {
+            self.allowed.push(category);
+        }
+        self
+    }
+
+    pub fn forbid(mut self, category: EventCategory) -> DropPolicy {
+        self.allowed.retain(|&allowed| allowed != category);
+        self
+    }
+
+    pub fn may_drop(&self, event: &glutin::Event) -> bool {
+        let category = EventCategory::of(event);
+        self.allowed.contains(&category) && !category.is_coalesce_only()
+    }
+
+    pub fn may_coalesce(&self, event: &glutin::Event) -> bool {
+        self.allowed.contains(&EventCategory::of(event))
+    }
+
+    /// Merge `event` into `last`, if the policy allows it and the pair can
+    /// be summarized by one event, such as two cursor motions.
+    pub fn coalesce(&self, last: &mut glutin::Event, event: &glutin::Event) -> bool {
+        use glutin::Event::*;
+        use glutin::MouseScrollDelta::{LineDelta, PixelDelta};
+        if !self.may_coalesce(last) || !self.may_coalesce(event) {
+            return false;
+        }
+        let merged = match (&*last, event) {
+            (&MouseMoved(..), &MouseMoved(..)) |
+            (&Resized(..), &Resized(..)) |
+            (&Moved(..), &Moved(..)) |
+            (&TouchpadPressure(..), &TouchpadPressure(..)) => event.clone(),
+            (&MouseWheel(LineDelta(x0, y0), phase),
+             &MouseWheel(LineDelta(x1, y1), _)) =>
+                MouseWheel(LineDelta(x0 + x1, y0 + y1), phase),
+            (&MouseWheel(PixelDelta(x0, y0), phase),
+             &MouseWheel(PixelDelta(x1, y1), _)) =>
+                MouseWheel(PixelDelta(x0 + x1, y0 + y1), phase),
+            _ => return false
+        };
+        *last = merged;
+        true
+    }
+
+    /// Coalesce runs of mergeable events in a batch.
+    pub fn coalesce_batch(&self, events: Vec<glutin::Event>) -> Vec<glutin::Event> {
+        let mut batch: Vec<glutin::Event> = Vec::with_capacity(events.len());
+        for event in events {
+            let merged = batch.last_mut()
+                .is_some_and(|last| self.coalesce(last, &event));
+            if !merged {
+                batch.push(event);
+            }
+        }
+        batch
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use glutin::Event::{MouseMoved, ReceivedCharacter, Resized};
+    use super::{DropPolicy, EventCategory};
+
+    #[test]
+    fn critical_categories_can_not_be_allowed() {
+        let policy = DropPolicy::none().allow(EventCategory::Text);
+        assert!(!policy.may_drop(&ReceivedCharacter('a')));
+        assert!(DropPolicy::default().may_drop(&MouseMoved(0, 0)));
+        assert!(!DropPolicy::default().may_drop(&Resized(1, 1)));
+        assert!(DropPolicy::default().may_coalesce(&Resized(1, 1)));
+        let policy = DropPolicy::default().forbid(EventCategory::Motion);
+        assert!(!policy.may_drop(&MouseMoved(0, 0)));
+    }
+
+    #[test]
+    fn coalesces_runs_of_motion() {
+        let batch = DropPolicy::default().coalesce_batch(vec![
+            MouseMoved(1, 1), MouseMoved(2, 2), ReceivedCharacter('a'), MouseMoved(3, 3)
+        ]);
+        let expected = vec![MouseMoved(2, 2), ReceivedCharacter('a'), MouseMoved(3, 3)];
+        assert_eq!(format!("{:?}", batch), format!("{:?}", expected));
+    }
+}
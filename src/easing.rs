@@ -0,0 +1,115 @@
+//! Easing curves.
+//!
+//! Each curve maps normalized time in `[0, 1]` to progress, with `0` at `0`
+//! and `1` at `1`. They can be passed to `anim::tween` or applied to any
+//! normalized time signal with `ease`.
+
+use std::f64::consts::PI;
+use carboxyl::Signal;
+
+pub fn linear(t: f64) -> f64 {
+    t
+}
+
+pub fn quad_in(t: f64) -> f64 {
+    t * t
+}
+
+pub fn quad_out(t: f64) -> f64 {
+    1.0 - quad_in(1.0 - t)
+}
+
+pub fn quad_in_out(t: f64) -> f64 {
+    in_out(quad_in, t)
+}
+
+pub fn cubic_in(t: f64) -> f64 {
+    t * t * t
+}
+
+pub fn cubic_out(t: f64) -> f64 {
+    1.0 - cubic_in(1.0 - t)
+}
+
+pub fn cubic_in_out(t: f64) -> f64 {
+    in_out(cubic_in, t)
+}
+
+/// Overshoots and oscillates into place.
+pub fn elastic_out(t: f64) -> f64 {
+    if t <= 0.0 || t >= 1.0 {
+        return t.clamp(0.0, 1.0);
+    }
+    2f64.powf(-10.0 * t) * ((t * 10.0 - 0.75) * (2.0 * PI / 3.0)).sin() + 1.0
+}
+
+pub fn elastic_in(t: f64) -> f64 {
+    1.0 - elastic_out(1.0 - t)
+}
+
+/// Bounces off the end a few times, like a dropped ball.
+pub fn bounce_out(t: f64) -> f64 {
+    const N: f64 = 7.5625;
+    const D: f64 = 2.75;
+    if t < 1.0 / D {
+        N * t * t
+    } else if t < 2.0 / D {
+        let t = t - 1.5 / D;
+        N * t * t + 0.75
+    } else if t < 2.5 / D {
+        let t = t - 2.25 / D;
+        N * t * t + 0.9375
+    } else {
+        let t = t - 2.625 / D;
+        N * t * t + 0.984375
+    }
+}
+
+pub fn bounce_in(t: f64) -> f64 {
+    1.0 - bounce_out(1.0 - t)
+}
+
+/// Run an ease-in curve for the first half and its mirror for the second.
+fn in_out(ease_in: fn(f64) -> f64, t: f64) -> f64 {
+    if t < 0.5 {
+        ease_in(2.0 * t) / 2.0
+    } else {
+        1.0 - ease_in(2.0 - 2.0 * t) / 2.0
+    }
+}
+
+/// Apply `easing` to a normalized time signal.
+pub fn ease(time: &Signal<f64>, easing: fn(f64) -> f64) -> Signal<f64> {
+    time.map(move |t| easing(t.clamp(0.0, 1.0)))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CURVES: [fn(f64) -> f64; 11] = [
+        linear, quad_in, quad_out, quad_in_out, cubic_in, cubic_out, cubic_in_out,
+        elastic_in, elastic_out, bounce_in, bounce_out
+    ];
+
+    #[test]
+    fn curves_start_at_zero_and_end_at_one() {
+        for curve in CURVES.iter() {
+            assert!(curve(0.0).abs() < 1e-9);
+            assert!((curve(1.0) - 1.0).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn in_out_curves_are_symmetric() {
+        assert_eq!(quad_in_out(0.5), 0.5);
+        assert!((cubic_in_out(0.25) + cubic_in_out(0.75) - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn eases_a_clamped_time_signal() {
+        let time = Signal::new(2.0);
+        assert_eq!(ease(&time, quad_in).sample(), 1.0);
+    }
+}
@@ -0,0 +1,291 @@
+use std::sync::{Arc, RwLock};
+use carboxyl::{Signal, Sink, Stream};
+use glutin::VirtualKeyCode;
+use ::{Event, Button, Modifiers};
+
+/// A change of the focused widget.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FocusChange {
+    pub from: Option<String>,
+    pub to: Option<String>
+}
+
+/// The widgets that Tab moves focus between, in order.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TabOrder {
+    pub focusables: Vec<String>,
+    /// Whether moving past the last widget continues at the first one and
+    /// vice versa, rather than staying put.
+    pub wrap: bool
+}
+
+impl Default for TabOrder {
+    fn default() -> TabOrder {
+        TabOrder { focusables: Vec::new(), wrap: true }
+    }
+}
+
+impl TabOrder {
+    /// The widget after (or before) `current`.
+    ///
+    /// Without a focused widget in the order, this starts at the first (or
+    /// last) one.
+    pub fn step(&self, current: Option<String>, backwards: bool) -> Option<String> {
+        let count = self.focusables.len();
+        if count == 0 {
+            return current;
+        }
+        let index = current.as_ref().and_then(|current|
+            self.focusables.iter().position(|name| name == current));
+        let next = match (index, backwards) {
+            (None, false) => 0,
+            (None, true) => count - 1,
+            (Some(index), false) if index + 1 < count => index + 1,
+            (Some(index), true) if index > 0 => index - 1,
+            (Some(_), false) if self.wrap => 0,
+            (Some(_), true) if self.wrap => count - 1,
+            (Some(index), _) => index
+        };
+        Some(self.focusables[next].clone())
+    }
+}
+
+#[derive(Clone)]
+pub enum FocusUpdate {
+    Set(Option<String>),
+    Next,
+    Previous
+}
+
+impl FocusUpdate {
+    pub fn apply(self, current: Option<String>, order: &TabOrder) -> Option<String> {
+        match self {
+            FocusUpdate::Set(focus) => focus,
+            FocusUpdate::Next => order.step(current, false),
+            FocusUpdate::Previous => order.step(current, true)
+        }
+    }
+}
+
+fn is_keyboard(event: &Event) -> bool {
+    matches!(*event,
+        Event::Press(Button::Keyboard(_)) | Event::Release(Button::Keyboard(_)) |
+        Event::Text(_))
+}
+
+/// Whether an event belongs to Tab navigation rather than to a widget.
+fn is_tab(event: &Event) -> bool {
+    match *event {
+        Event::Press(Button::Keyboard(key)) | Event::Release(Button::Keyboard(key)) =>
+            key == VirtualKeyCode::Tab,
+        Event::Text(ref text) => text == "\t",
+        _ => false
+    }
+}
+
+/// Tracks which logical widget has keyboard focus and routes keyboard
+/// events to it.
+///
+/// Widgets are identified by name. Nothing has focus initially. Tab and
+/// Shift+Tab move focus through the registered widgets, so Tab key events
+/// and tab characters are not routed to widgets. `modifiers` should be
+/// tracked from all of the window's events, such as
+/// `WindowDriver::modifiers`.
+#[derive(Clone)]
+pub struct FocusManager {
+    update_sink: Sink<FocusUpdate>,
+    order: Arc<RwLock<TabOrder>>,
+    focused: Signal<Option<String>>,
+    changes: Stream<FocusChange>,
+    keyboard: Stream<Event>
+}
+
+impl FocusManager {
+    pub fn new(events: &Stream<Event>, modifiers: &Signal<Modifiers>) -> FocusManager {
+        let update_sink = Sink::new();
+        let order = Arc::new(RwLock::new(TabOrder::default()));
+        let tabs = modifiers
+            .snapshot(events, |modifiers, event| match event {
+                Event::Press(Button::Keyboard(VirtualKeyCode::Tab)) =>
+                    Some(if modifiers.shift() {
+                        FocusUpdate::Previous
+                    } else {
+                        FocusUpdate::Next
+                    }),
+                _ => None
+            })
+            .filter_some();
+        let updates = update_sink.stream().merge(&tabs);
+        let fold_order = order.clone();
+        let focused = updates.fold(None, move |current, update: FocusUpdate|
+            update.apply(current, &fold_order.read().unwrap()));
+        // Snapshots see the focus from before the update is folded in
+        let change_order = order.clone();
+        let changes = focused
+            .snapshot(&updates, move |from, update| {
+                let to = update.apply(from.clone(), &change_order.read().unwrap());
+                FocusChange { from: from, to: to }
+            })
+            .filter(|change| change.from != change.to);
+        FocusManager {
+            update_sink: update_sink,
+            order: order,
+            focused: focused,
+            changes: changes,
+            keyboard: events.filter(|event| is_keyboard(event) && !is_tab(event))
+        }
+    }
+
+    /// Add the widget `name` to the end of the tab order.
+    pub fn register(&self, name: &str) {
+        self.unregister(name);
+        self.order.write().unwrap().focusables.push(name.to_string());
+    }
+
+    pub fn unregister(&self, name: &str) {
+        self.order.write().unwrap().focusables.retain(|focusable| focusable != name);
+    }
+
+    /// Whether Tab past the last widget wraps around to the first (the
+    /// default), and Shift+Tab past the first to the last.
+    pub fn set_wrap(&self, wrap: bool) {
+        self.order.write().unwrap().wrap = wrap;
+    }
+
+    /// Move focus to the next widget in the tab order, as Tab does.
+    pub fn focus_next(&self) {
+        self.update_sink.send(FocusUpdate::Next);
+    }
+
+    /// Move focus to the previous widget in the tab order, as Shift+Tab does.
+    pub fn focus_previous(&self) {
+        self.update_sink.send(FocusUpdate::Previous);
+    }
+
+    /// Give focus to the widget `name`, or to none.
+    pub fn focus(&self, name: Option<&str>) {
+        self.update_sink.send(FocusUpdate::Set(name.map(|name| name.to_string())));
+    }
+
+    pub fn focused(&self) -> Signal<Option<String>> {
+        self.focused.clone()
+    }
+
+    /// Fires whenever focus moves to another widget, whether requested
+    /// directly or by Tab navigation.
+    pub fn changes(&self) -> Stream<FocusChange> {
+        self.changes.clone()
+    }
+
+    /// Whether the widget `name` has focus.
+    pub fn has_focus(&self, name: &str) -> Signal<bool> {
+        let name = name.to_string();
+        self.focused.map(move |focused| focused.as_ref() == Some(&name))
+    }
+
+    /// Key presses, releases and text input while the widget `name` has
+    /// focus.
+    pub fn keyboard(&self, name: &str) -> Stream<Event> {
+        self.has_focus(name)
+            .snapshot(&self.keyboard, |focused, event|
+                if focused { Some(event) } else { None })
+            .filter_some()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use carboxyl::{Signal, Sink, Stream};
+    use glutin::VirtualKeyCode::{A, Tab, LShift, RShift};
+    use glutin::MouseButton::Left;
+    use ::{Button, Event, Modifiers};
+    use super::{FocusManager, FocusChange, TabOrder};
+
+    fn focus_manager(events: &Stream<Event>) -> FocusManager {
+        let modifiers: Signal<Modifiers> = events
+            .fold(Modifiers::default(), |modifiers, event| modifiers.update(&event));
+        FocusManager::new(events, &modifiers)
+    }
+
+    #[test]
+    fn routes_keyboard_events_to_the_focused_widget() {
+        let events = Sink::new();
+        let focus = focus_manager(&events.stream());
+        let first = focus.keyboard("first").fold(0, |n, _| n + 1);
+        let second = focus.keyboard("second").fold(0, |n, _| n + 1);
+        events.send(Event::Text("a".to_string()));
+        focus.focus(Some("first"));
+        events.send(Event::Press(Button::Keyboard(A)));
+        events.send(Event::Press(Button::Mouse(Left)));
+        focus.focus(Some("second"));
+        events.send(Event::Text("b".to_string()));
+        assert_eq!((first.sample(), second.sample()), (1, 1));
+    }
+
+    #[test]
+    fn reports_only_actual_focus_changes() {
+        let events = Sink::new();
+        let focus = focus_manager(&events.stream());
+        let changes = focus.changes().fold(vec![], |mut changes, change| {
+            changes.push(change);
+            changes
+        });
+        focus.focus(Some("field"));
+        focus.focus(Some("field"));
+        focus.focus(None);
+        assert_eq!(changes.sample(), vec![
+            FocusChange { from: None, to: Some("field".to_string()) },
+            FocusChange { from: Some("field".to_string()), to: None }
+        ]);
+    }
+
+    #[test]
+    fn steps_through_the_tab_order() {
+        let mut order = TabOrder {
+            focusables: vec!["a".to_string(), "b".to_string()],
+            wrap: true
+        };
+        let step = |order: &TabOrder, current: Option<&str>, backwards| order
+            .step(current.map(|name| name.to_string()), backwards);
+        assert_eq!(step(&order, None, false), Some("a".to_string()));
+        assert_eq!(step(&order, None, true), Some("b".to_string()));
+        assert_eq!(step(&order, Some("b"), false), Some("a".to_string()));
+        order.wrap = false;
+        assert_eq!(step(&order, Some("b"), false), Some("b".to_string()));
+        assert_eq!(step(&order, Some("a"), true), Some("a".to_string()));
+    }
+
+    #[test]
+    fn tab_and_shift_tab_move_focus() {
+        let events = Sink::new();
+        let focus = focus_manager(&events.stream());
+        for name in &["a", "b", "c"] {
+            focus.register(name);
+        }
+        let keys = focus.keyboard("a").fold(0, |n, _| n + 1);
+        events.send(Event::Press(Button::Keyboard(Tab)));
+        events.send(Event::Text("\t".to_string()));
+        events.send(Event::Press(Button::Keyboard(Tab)));
+        assert_eq!(focus.focused().sample(), Some("b".to_string()));
+        events.send(Event::Press(Button::Keyboard(LShift)));
+        events.send(Event::Press(Button::Keyboard(Tab)));
+        assert_eq!(focus.focused().sample(), Some("a".to_string()));
+        assert_eq!(keys.sample(), 0);
+    }
+
+    #[test]
+    fn shift_tab_goes_back_while_either_shift_is_held() {
+        let events = Sink::new();
+        let focus = focus_manager(&events.stream());
+        for name in &["a", "b", "c"] {
+            focus.register(name);
+        }
+        focus.focus(Some("b"));
+        events.send(Event::Press(Button::Keyboard(LShift)));
+        events.send(Event::Press(Button::Keyboard(RShift)));
+        events.send(Event::Release(Button::Keyboard(LShift)));
+        events.send(Event::Press(Button::Keyboard(Tab)));
+        assert_eq!(focus.focused().sample(), Some("a".to_string()));
+    }
+}
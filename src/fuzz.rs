@@ -0,0 +1,257 @@
+use glutin::{self, ElementState, MouseButton, MouseScrollDelta, TouchPhase,
+             VirtualKeyCode};
+use source::EventSource;
+
+pub const KEYS: [VirtualKeyCode; 14] = [
+    VirtualKeyCode::A, VirtualKeyCode::S, VirtualKeyCode::D, VirtualKeyCode::W,
+    VirtualKeyCode::Space, VirtualKeyCode::Return, VirtualKeyCode::Back,
+    VirtualKeyCode::Tab, VirtualKeyCode::Escape, VirtualKeyCode::LShift,
+    VirtualKeyCode::LControl, VirtualKeyCode::Left, VirtualKeyCode::Right,
+    VirtualKeyCode::Capital
+];
+
+const BUTTONS: [MouseButton; 3] =
+    [MouseButton::Left, MouseButton::Right, MouseButton::Middle];
+
+/// Settings of a `FuzzSource`. Category weights are relative to each other; a
+/// weight of zero disables the category.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FuzzConfig {
+    pub seed: u64,
+    /// Maximum number of events per poll.
+    pub max_events: usize,
+    /// Window size to start with and to keep cursor positions inside.
+    pub size: (u32, u32),
+    pub keyboard: u32,
+    pub mouse_buttons: u32,
+    pub motion: u32,
+    pub wheel: u32,
+    pub text: u32,
+    pub touch: u32,
+    pub window: u32
+}
+
+impl Default for FuzzConfig {
+    fn default() -> FuzzConfig {
+        FuzzConfig {
+            seed: 1,
+            max_events: 8,
+            size: (640, 480),
+            keyboard: 4,
+            mouse_buttons: 2,
+            motion: 8,
+            wheel: 2,
+            text: 3,
+            touch: 1,
+            window: 1
+        }
+    }
+}
+
+/// An event source producing random but well-formed event sequences.
+///
+/// Keys and buttons are only released after having been pressed, touches go
+/// through started, moved and ended phases, and the window is never closed.
+/// The same configuration always produces the same sequence.
+pub struct FuzzSource {
+    config: FuzzConfig,
+    state: u64,
+    size: (u32, u32),
+    focused: bool,
+    keys: Vec<VirtualKeyCode>,
+    buttons: Vec<MouseButton>,
+    touches: Vec<(u64, (f64, f64))>,
+    next_touch: u64
+}
+
+impl FuzzSource {
+    pub fn new(config: FuzzConfig) -> FuzzSource {
+        FuzzSource {
+            config: config,
+            state: config.seed.max(1),
+            size: config.size,
+            focused: true,
+            keys: Vec::new(),
+            buttons: Vec::new(),
+            touches: Vec::new(),
+            next_touch: 0
+        }
+    }
+
+    /// Generate a single event.
+    pub fn next_event(&mut self) -> glutin::Event {
+        let c = self.config;
+        let weights = [c.keyboard, c.mouse_buttons, c.motion, c.wheel, c.text,
+                       c.touch, c.window];
+        let total: u32 = weights.iter().sum();
+        assert!(total > 0, "all fuzz categories are disabled");
+        let mut pick = self.below(total as u64) as u32;
+        let mut category = 0;
+        while pick >= weights[category] {
+            pick -= weights[category];
+            category += 1;
+        }
+        match category {
+            0 => self.key(),
+            1 => self.mouse_button(),
+            2 => {
+                let (x, y) = self.position();
+                glutin::Event::MouseMoved(x as i32, y as i32)
+            },
+            3 => self.wheel(),
+            4 => glutin::Event::ReceivedCharacter(
+                (b' ' + self.below(95) as u8) as char),
+            5 => self.touch(),
+            _ => self.window()
+        }
+    }
+
+    /// xorshift64*
+    fn next(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    fn below(&mut self, n: u64) -> u64 {
+        self.next() % n
+    }
+
+    fn position(&mut self) -> (f64, f64) {
+        let x = self.below(self.size.0.max(1) as u64) as f64;
+        let y = self.below(self.size.1.max(1) as u64) as f64;
+        (x, y)
+    }
+
+    fn key(&mut self) -> glutin::Event {
+        if !self.keys.is_empty() && self.below(2) == 0 {
+            let index = self.below(self.keys.len() as u64) as usize;
+            let key = self.keys.swap_remove(index);
+            glutin::Event::KeyboardInput(ElementState::Released, 0, Some(key))
+        } else {
+            let key = KEYS[self.below(KEYS.len() as u64) as usize];
+            if !self.keys.contains(&key) {
+                self.keys.push(key);
+            }
+            glutin::Event::KeyboardInput(ElementState::Pressed, 0, Some(key))
+        }
+    }
+
+    fn mouse_button(&mut self) -> glutin::Event {
+        let button = BUTTONS[self.below(BUTTONS.len() as u64) as usize];
+        if let Some(index) = self.buttons.iter().position(|&b| b == button) {
+            self.buttons.swap_remove(index);
+            glutin::Event::MouseInput(ElementState::Released, button)
+        } else {
+            self.buttons.push(button);
+            glutin::Event::MouseInput(ElementState::Pressed, button)
+        }
+    }
+
+    fn wheel(&mut self) -> glutin::Event {
+        let amount = self.below(7) as f32 - 3.0;
+        let delta = if self.below(2) == 0 {
+            MouseScrollDelta::LineDelta(0.0, amount)
+        } else {
+            MouseScrollDelta::PixelDelta(amount * 7.0, amount * 13.0)
+        };
+        glutin::Event::MouseWheel(delta, TouchPhase::Moved)
+    }
+
+    fn touch(&mut self) -> glutin::Event {
+        let location = self.position();
+        let (id, phase) = match self.below(3) {
+            0 | 1 if !self.touches.is_empty() => {
+                let index = self.below(self.touches.len() as u64) as usize;
+                let id = self.touches[index].0;
+                if self.below(2) == 0 {
+                    self.touches[index].1 = location;
+                    (id, TouchPhase::Moved)
+                } else {
+                    self.touches.swap_remove(index);
+                    (id, TouchPhase::Ended)
+                }
+            },
+            _ => {
+                self.next_touch += 1;
+                self.touches.push((self.next_touch, location));
+                (self.next_touch, TouchPhase::Started)
+            }
+        };
+        glutin::Event::Touch(glutin::Touch {
+            phase: phase,
+            location: location,
+            id: id
+        })
+    }
+
+    fn window(&mut self) -> glutin::Event {
+        if self.below(3) == 0 {
+            self.focused = !self.focused;
+            if !self.focused {
+                self.keys.clear();
+                self.buttons.clear();
+            }
+            glutin::Event::Focused(self.focused)
+        } else {
+            self.size = (
+                1 + self.below(self.config.size.0.max(1) as u64 * 2) as u32,
+                1 + self.below(self.config.size.1.max(1) as u64 * 2) as u32
+            );
+            glutin::Event::Resized(self.size.0, self.size.1)
+        }
+    }
+}
+
+impl EventSource for FuzzSource {
+    fn poll_events(&mut self) -> Vec<glutin::Event> {
+        let count = self.below(self.config.max_events as u64 + 1) as usize;
+        (0..count).map(|_| self.next_event()).collect()
+    }
+
+    fn inner_size(&self) -> Option<(u32, u32)> {
+        Some(self.size)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use glutin::{ElementState, Event};
+    use source::EventSource;
+    use super::{FuzzSource, FuzzConfig};
+
+    fn events(config: FuzzConfig, polls: usize) -> Vec<Event> {
+        let mut source = FuzzSource::new(config);
+        (0..polls).flat_map(|_| source.poll_events()).collect()
+    }
+
+    #[test]
+    fn is_deterministic_for_a_seed() {
+        let config = FuzzConfig { seed: 42, .. FuzzConfig::default() };
+        assert_eq!(format!("{:?}", events(config, 50)),
+                   format!("{:?}", events(config, 50)));
+    }
+
+    #[test]
+    fn only_releases_pressed_keys() {
+        let config = FuzzConfig {
+            keyboard: 1, mouse_buttons: 0, motion: 0, wheel: 0, text: 0,
+            touch: 0, window: 0,
+            .. FuzzConfig::default()
+        };
+        let mut held = HashSet::new();
+        for event in events(config, 200) {
+            match event {
+                Event::KeyboardInput(ElementState::Pressed, _, Some(key)) => {
+                    held.insert(key);
+                },
+                Event::KeyboardInput(ElementState::Released, _, Some(key)) =>
+                    assert!(held.remove(&key)),
+                _ => panic!("unexpected event category")
+            }
+        }
+    }
+}
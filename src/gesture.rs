@@ -0,0 +1,210 @@
+use ::{PointerEvent, PointerAction, Timed};
+
+/// Distance in pixels a pointer may travel before a press becomes a drag.
+const DRAG_THRESHOLD: f64 = 4.0;
+
+/// A recognized gesture.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Gesture {
+    pub name: &'static str,
+    pub position: (f64, f64),
+    /// Movement since the gesture's previous event, for continuous gestures.
+    pub delta: (f64, f64)
+}
+
+impl Gesture {
+    pub fn new(name: &'static str, position: (f64, f64), delta: (f64, f64)) -> Gesture {
+        Gesture { name: name, position: position, delta: delta }
+    }
+}
+
+/// Recognizes a gesture from a sequence of pointer events.
+pub trait GestureRecognizer {
+    /// Look at the next pointer event and return a gesture if it completes
+    /// or continues one.
+    fn feed(&mut self, event: &Timed<PointerEvent>) -> Option<Gesture>;
+
+    /// Forget any partial gesture, because another recognizer claimed the
+    /// event sequence.
+    fn reset(&mut self);
+}
+
+fn distance(a: (f64, f64), b: (f64, f64)) -> f64 {
+    (a.0 - b.0).hypot(a.1 - b.1)
+}
+
+/// A press and release without moving away in between, named `"tap"`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TapRecognizer {
+    pressed: Option<(f64, f64)>
+}
+
+impl GestureRecognizer for TapRecognizer {
+    fn feed(&mut self, event: &Timed<PointerEvent>) -> Option<Gesture> {
+        let position = event.value.position;
+        let moved_away = |start| distance(start, position) > DRAG_THRESHOLD;
+        match event.value.action {
+            PointerAction::Press(_) => self.pressed = Some(position),
+            PointerAction::Move if self.pressed.is_some_and(moved_away) =>
+                self.pressed = None,
+            PointerAction::Release(_) if self.pressed.take().is_some() =>
+                return Some(Gesture::new("tap", position, (0.0, 0.0))),
+            _ => ()
+        }
+        None
+    }
+
+    fn reset(&mut self) {
+        self.pressed = None;
+    }
+}
+
+/// Movement while pressed, past a small threshold, named `"drag"`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DragRecognizer {
+    pressed: Option<(f64, f64)>,
+    dragging: bool
+}
+
+impl GestureRecognizer for DragRecognizer {
+    fn feed(&mut self, event: &Timed<PointerEvent>) -> Option<Gesture> {
+        let position = event.value.position;
+        match event.value.action {
+            PointerAction::Press(_) => {
+                self.pressed = Some(position);
+                self.dragging = false;
+            },
+            PointerAction::Move => if let Some(last) = self.pressed {
+                self.dragging |= distance(last, position) > DRAG_THRESHOLD;
+                if self.dragging {
+                    self.pressed = Some(position);
+                    let delta = (position.0 - last.0, position.1 - last.1);
+                    return Some(Gesture::new("drag", position, delta));
+                }
+            },
+            PointerAction::Release(_) => self.reset(),
+            _ => ()
+        }
+        None
+    }
+
+    fn reset(&mut self) {
+        self.pressed = None;
+        self.dragging = false;
+    }
+}
+
+/// Recognizers ordered by priority.
+///
+/// Every event is offered to the recognizers, highest priority first. The
+/// first one that recognizes a gesture wins and all others are reset, so
+/// that one event sequence never produces two conflicting gestures.
+pub struct GestureRegistry {
+    recognizers: Vec<(i32, Box<dyn GestureRecognizer>)>
+}
+
+impl Default for GestureRegistry {
+    fn default() -> GestureRegistry {
+        GestureRegistry::new()
+    }
+}
+
+impl GestureRegistry {
+    /// A registry with the built-in tap (priority 0) and drag (priority 10)
+    /// recognizers, so that a drag cancels the tap it started with.
+    pub fn new() -> GestureRegistry {
+        let mut registry = GestureRegistry { recognizers: Vec::new() };
+        registry.add(0, TapRecognizer::default());
+        registry.add(10, DragRecognizer::default());
+        registry
+    }
+
+    /// Add a recognizer. Among equal priorities, earlier ones go first.
+    pub fn add<R>(&mut self, priority: i32, recognizer: R)
+        where R: GestureRecognizer + 'static
+    {
+        let index = self.recognizers.iter()
+            .position(|&(other, _)| other < priority)
+            .unwrap_or(self.recognizers.len());
+        self.recognizers.insert(index, (priority, Box::new(recognizer)));
+    }
+
+    /// Offer an event to the recognizers and return the winning gesture.
+    pub fn feed(&mut self, event: &Timed<PointerEvent>) -> Option<Gesture> {
+        let recognized = self.recognizers.iter_mut()
+            .enumerate()
+            .filter_map(|(index, recognizer)|
+                recognizer.1.feed(event).map(|gesture| (index, gesture)))
+            .next();
+        recognized.map(|(winner, gesture)| {
+            for (index, recognizer) in self.recognizers.iter_mut().enumerate() {
+                if index != winner {
+                    recognizer.1.reset();
+                }
+            }
+            gesture
+        })
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use glutin::MouseButton::Left;
+    use ::{PointerEvent, PointerAction, Timed};
+    use super::*;
+
+    fn event(position: (f64, f64), action: PointerAction) -> Timed<PointerEvent> {
+        Timed::new(0, 0, PointerEvent::new(position, action))
+    }
+
+    fn names(registry: &mut GestureRegistry, events: Vec<Timed<PointerEvent>>)
+        -> Vec<&'static str>
+    {
+        events.iter().filter_map(|event| registry.feed(event)).map(|g| g.name).collect()
+    }
+
+    #[test]
+    fn taps_without_movement() {
+        let mut registry = GestureRegistry::new();
+        let events = vec![
+            event((1.0, 1.0), PointerAction::Press(Left)),
+            event((2.0, 1.0), PointerAction::Move),
+            event((2.0, 1.0), PointerAction::Release(Left))
+        ];
+        assert_eq!(names(&mut registry, events), vec!["tap"]);
+    }
+
+    #[test]
+    fn a_drag_cancels_the_tap() {
+        let mut registry = GestureRegistry::new();
+        let events = vec![
+            event((0.0, 0.0), PointerAction::Press(Left)),
+            event((10.0, 0.0), PointerAction::Move),
+            event((12.0, 0.0), PointerAction::Move),
+            event((12.0, 0.0), PointerAction::Release(Left))
+        ];
+        assert_eq!(names(&mut registry, events), vec!["drag", "drag"]);
+    }
+
+    struct Anything;
+
+    impl GestureRecognizer for Anything {
+        fn feed(&mut self, event: &Timed<PointerEvent>) -> Option<Gesture> {
+            Some(Gesture::new("any", event.value.position, (0.0, 0.0)))
+        }
+
+        fn reset(&mut self) {}
+    }
+
+    #[test]
+    fn higher_priority_recognizers_win() {
+        let mut registry = GestureRegistry::new();
+        registry.add(20, Anything);
+        let events = vec![
+            event((0.0, 0.0), PointerAction::Press(Left)),
+            event((0.0, 0.0), PointerAction::Release(Left))
+        ];
+        assert_eq!(names(&mut registry, events), vec!["any", "any"]);
+    }
+}
@@ -0,0 +1,52 @@
+/// How long a button has been held, in nanoseconds.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct Hold {
+    /// When the button went down, if it is held.
+    pub since: Option<u64>,
+    pub duration: u64
+}
+
+#[derive(Clone)]
+pub enum HoldUpdate {
+    Press(u64),
+    Release,
+    Advance(u64)
+}
+
+impl HoldUpdate {
+    pub fn apply(self, current: Hold) -> Hold {
+        use self::HoldUpdate::*;
+        match self {
+            // Key repeat sends further presses, which must not restart
+            Press(time) => Hold { since: current.since.or(Some(time)), .. current },
+            Release => Hold::default(),
+            Advance(now) => Hold {
+                duration: current.since.map_or(0, |since| now.saturating_sub(since)),
+                .. current
+            }
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::{Hold, HoldUpdate};
+    use super::HoldUpdate::*;
+
+    fn run(updates: Vec<HoldUpdate>) -> Hold {
+        updates.into_iter().fold(Hold::default(), |state, update| update.apply(state))
+    }
+
+    #[test]
+    fn measures_from_the_first_press() {
+        let hold = run(vec![Press(10), Advance(20), Press(25), Advance(40)]);
+        assert_eq!(hold.duration, 30);
+    }
+
+    #[test]
+    fn resets_on_release() {
+        assert_eq!(run(vec![Press(10), Advance(20), Release]), Hold::default());
+        assert_eq!(run(vec![Press(10), Release, Advance(50)]).duration, 0);
+    }
+}
@@ -0,0 +1,51 @@
+use std::time::Duration;
+
+/// Lowers the frame rate of the run loop while there is no user input.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct IdlePolicy {
+    /// Time without input events after which the window counts as idle.
+    pub timeout: Duration,
+    /// Frame rate to use while idle.
+    pub fps: f64
+}
+
+impl IdlePolicy {
+    pub fn new(timeout: Duration, fps: f64) -> IdlePolicy {
+        assert!(fps > 0.0);
+        IdlePolicy { timeout: timeout, fps: fps }
+    }
+
+    /// Target frame rate given the active rate and the time since the last
+    /// input event in nanoseconds.
+    pub fn target_fps(&self, active_fps: f64, idle_ns: u64) -> f64 {
+        if idle_ns >= nanoseconds(self.timeout) {
+            self.fps.min(active_fps)
+        } else {
+            active_fps
+        }
+    }
+}
+
+pub fn nanoseconds(duration: Duration) -> u64 {
+    duration.as_secs() * 1_000_000_000 + duration.subsec_nanos() as u64
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use super::IdlePolicy;
+
+    #[test]
+    fn throttles_only_after_the_timeout() {
+        let policy = IdlePolicy::new(Duration::from_secs(2), 10.0);
+        assert_eq!(policy.target_fps(60.0, 1_999_999_999), 60.0);
+        assert_eq!(policy.target_fps(60.0, 2_000_000_000), 10.0);
+    }
+
+    #[test]
+    fn never_raises_the_frame_rate() {
+        let policy = IdlePolicy::new(Duration::from_secs(0), 30.0);
+        assert_eq!(policy.target_fps(5.0, 0), 5.0);
+    }
+}
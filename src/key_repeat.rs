@@ -0,0 +1,165 @@
+use std::time::Duration;
+use glutin::VirtualKeyCode;
+use idle::nanoseconds;
+
+/// Timing of emulated key repeat.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct KeyRepeat {
+    /// Time a key has to be held before it starts repeating.
+    pub delay: Duration,
+    /// Repeats per second after that.
+    pub rate: f64
+}
+
+impl KeyRepeat {
+    pub fn new(delay: Duration, rate: f64) -> KeyRepeat {
+        assert!(rate > 0.0);
+        KeyRepeat { delay: delay, rate: rate }
+    }
+
+    fn interval(&self) -> u64 {
+        ((1e9 / self.rate) as u64).max(1)
+    }
+}
+
+/// Most repeats sent at once, so that a tick after a long stall (e.g. a
+/// suspend) does not catch up with a burst.
+const MAX_CATCH_UP: u64 = 3;
+
+/// Tracks held keys to generate repeats for the most recently pressed one,
+/// as operating systems do.
+#[derive(Clone, Debug, Default)]
+pub struct Repeater {
+    held: Vec<VirtualKeyCode>,
+    /// The repeating key and when it is next due.
+    repeating: Option<(VirtualKeyCode, u64)>,
+    /// Text the repeating key typed when it was pressed.
+    text: String,
+    /// Whether received characters follow a new press (`Some(true)`), whose
+    /// text they are, or a repeat sent by the backend (`Some(false)`).
+    typing: Option<bool>
+}
+
+impl Repeater {
+    /// Register a press and return whether it is a new one, rather than a
+    /// repeat sent by the backend.
+    pub fn press(&mut self, key: VirtualKeyCode, time: u64, config: &KeyRepeat)
+        -> bool
+    {
+        if self.held.contains(&key) {
+            self.typing = Some(false);
+            return false;
+        }
+        self.held.push(key);
+        self.repeating = Some((key, time + nanoseconds(config.delay)));
+        self.text.clear();
+        self.typing = Some(true);
+        true
+    }
+
+    /// Register a received character and return whether it was typed by a
+    /// repeat sent by the backend.
+    pub fn character(&mut self, c: char) -> bool {
+        match self.typing {
+            Some(true) => {
+                self.text.push(c);
+                false
+            },
+            Some(false) => true,
+            None => false
+        }
+    }
+
+    /// Register any other event, which ends the text of the last press.
+    pub fn interrupt(&mut self) {
+        self.typing = None;
+    }
+
+    /// The text the repeating key typed when it was pressed.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn release(&mut self, key: VirtualKeyCode) {
+        self.typing = None;
+        self.held.retain(|&held| held != key);
+        if self.repeating.is_some_and(|(repeating, _)| repeating == key) {
+            self.repeating = None;
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.held.clear();
+        self.repeating = None;
+        self.typing = None;
+    }
+
+    /// The key to repeat and how often it is due at `now`, at most
+    /// `MAX_CATCH_UP` times.
+    pub fn due(&mut self, now: u64, config: &KeyRepeat)
+        -> Option<(VirtualKeyCode, u64)>
+    {
+        let (key, next) = self.repeating?;
+        if next > now {
+            return None;
+        }
+        let interval = config.interval();
+        let count = (now - next) / interval + 1;
+        self.repeating = Some((key, next + count * interval));
+        Some((key, count.min(MAX_CATCH_UP)))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use glutin::VirtualKeyCode::{A, B};
+    use super::{KeyRepeat, Repeater};
+
+    fn config() -> KeyRepeat {
+        KeyRepeat::new(Duration::from_millis(500), 10.0)
+    }
+
+    #[test]
+    fn repeats_after_the_delay_at_the_rate() {
+        let mut repeater = Repeater::default();
+        assert!(repeater.press(A, 0, &config()));
+        assert!(!repeater.press(A, 100, &config()));
+        assert_eq!(repeater.due(499_999_999, &config()), None);
+        assert_eq!(repeater.due(500_000_000, &config()), Some((A, 1)));
+        assert_eq!(repeater.due(750_000_000, &config()), Some((A, 2)));
+        repeater.release(A);
+        assert_eq!(repeater.due(2_000_000_000, &config()), None);
+    }
+
+    #[test]
+    fn does_not_catch_up_with_a_burst() {
+        let mut repeater = Repeater::default();
+        repeater.press(A, 0, &config());
+        assert_eq!(repeater.due(60_000_000_000, &config()), Some((A, 3)));
+        assert_eq!(repeater.due(60_050_000_000, &config()), None);
+        assert_eq!(repeater.due(60_100_000_000, &config()), Some((A, 1)));
+    }
+
+    #[test]
+    fn keeps_the_text_of_the_press_and_drops_repeated_text() {
+        let mut repeater = Repeater::default();
+        repeater.press(A, 0, &config());
+        assert!(!repeater.character('a'));
+        repeater.interrupt();
+        assert!(!repeater.character('b'));
+        assert!(!repeater.press(A, 100, &config()));
+        assert!(repeater.character('a'));
+        assert_eq!(repeater.text(), "a");
+    }
+
+    #[test]
+    fn only_the_last_pressed_key_repeats() {
+        let mut repeater = Repeater::default();
+        repeater.press(A, 0, &config());
+        repeater.press(B, 100, &config());
+        repeater.release(A);
+        assert_eq!(repeater.due(600_000_000, &config()).map(|(key, _)| key), Some(B));
+    }
+}
@@ -0,0 +1,105 @@
+use std::collections::VecDeque;
+use carboxyl::{Signal, Stream};
+use ::Timed;
+
+/// Latency statistics over a window of recent samples, in nanoseconds.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct LatencyStats {
+    pub count: usize,
+    pub p50: u64,
+    pub p90: u64,
+    pub p99: u64,
+    pub max: u64
+}
+
+impl LatencyStats {
+    pub fn from_samples(samples: &[u64]) -> LatencyStats {
+        let mut sorted = samples.to_vec();
+        sorted.sort();
+        LatencyStats::from_sorted(&sorted)
+    }
+
+    fn from_sorted(sorted: &[u64]) -> LatencyStats {
+        if sorted.is_empty() {
+            return LatencyStats::default();
+        }
+        let percentile = |p: usize| sorted[(sorted.len() - 1) * p / 100];
+        LatencyStats {
+            count: sorted.len(),
+            p50: percentile(50),
+            p90: percentile(90),
+            p99: percentile(99),
+            max: sorted[sorted.len() - 1]
+        }
+    }
+}
+
+/// The latest samples, both in arrival order and sorted, so that neither
+/// evicting nor computing percentiles needs a sort.
+#[derive(Clone, Debug, Default)]
+struct Samples {
+    recent: VecDeque<u64>,
+    sorted: Vec<u64>
+}
+
+impl Samples {
+    fn push(mut self, sample: u64, window: usize) -> Samples {
+        if self.recent.len() == window {
+            if let Some(oldest) = self.recent.pop_front() {
+                if let Ok(index) = self.sorted.binary_search(&oldest) {
+                    self.sorted.remove(index);
+                }
+            }
+        }
+        self.recent.push_back(sample);
+        let index = self.sorted.binary_search(&sample).unwrap_or_else(|index| index);
+        self.sorted.insert(index, sample);
+        self
+    }
+}
+
+/// Measure how long values take from being dispatched to arriving on
+/// `stream`, over the last `window` values.
+///
+/// `clock` must be the one the values were timed with, such as the driver's,
+/// which `WindowDriver::latency_probe` passes. Register the probe on the
+/// stream that is actually consumed, so that the cost of everything upstream
+/// of it is included.
+pub fn latency_probe<T, C>(stream: &Stream<Timed<T>>, window: usize, clock: C)
+    -> Signal<LatencyStats>
+    where T: Clone + Send + Sync + 'static,
+          C: Fn() -> u64 + Send + Sync + 'static
+{
+    assert!(window > 0);
+    stream
+        .map(move |timed| clock().saturating_sub(timed.time))
+        .fold(Samples::default(), move |samples, latency| samples.push(latency, window))
+        .map(|samples| LatencyStats::from_sorted(&samples.sorted))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use carboxyl::Sink;
+    use ::Timed;
+    use super::{LatencyStats, latency_probe};
+
+    #[test]
+    fn computes_percentiles_of_samples() {
+        let samples: Vec<u64> = (1..101).collect();
+        let stats = LatencyStats::from_samples(&samples);
+        assert_eq!(stats.count, 100);
+        assert_eq!((stats.p50, stats.p90, stats.p99, stats.max), (50, 90, 99, 100));
+    }
+
+    #[test]
+    fn keeps_only_the_latest_samples() {
+        let sink = Sink::new();
+        let stats = latency_probe(&sink.stream(), 2, || 10);
+        for (seq, time) in [9, 0, 8, 5].iter().enumerate() {
+            sink.send(Timed::new(*time, seq as u64, ()));
+        }
+        let stats = stats.sample();
+        assert_eq!((stats.count, stats.p50, stats.max), (2, 2, 5));
+    }
+}
@@ -0,0 +1,172 @@
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use carboxyl::{Signal, Stream};
+use ::{Button, Event, Timed};
+
+/// Decides whether a layer swallows an event, hiding it from lower layers.
+pub type Swallow = Box<dyn Fn(&Event) -> bool + Send + Sync>;
+
+struct Layer {
+    id: u64,
+    priority: i32,
+    swallow: Swallow
+}
+
+#[derive(Default)]
+struct Stack {
+    layers: Vec<Layer>,
+    /// The layers that received the press of each held button.
+    held: HashMap<Button, Vec<u64>>,
+    next_id: u64
+}
+
+impl Stack {
+    /// The ids of the layers that receive `event`, topmost first.
+    ///
+    /// A release also goes to every layer that received its press, even if
+    /// a layer above swallows it by now, so that no layer keeps it held.
+    fn receivers(&mut self, event: &Event) -> Vec<u64> {
+        let mut receivers = vec![];
+        for layer in &self.layers {
+            receivers.push(layer.id);
+            if (layer.swallow)(event) {
+                break;
+            }
+        }
+        match *event {
+            Event::Press(button) => {
+                self.held.insert(button, receivers.clone());
+            },
+            Event::Release(button) => {
+                for id in self.held.remove(&button).unwrap_or_default() {
+                    if !receivers.contains(&id) {
+                        receivers.push(id);
+                    }
+                }
+            },
+            _ => ()
+        }
+        receivers
+    }
+}
+
+/// A stack of event consumers, such as a game under a console overlay under
+/// a modal dialog.
+///
+/// Each event goes to the layers from the highest priority down, until one
+/// of them swallows it.
+#[derive(Clone)]
+pub struct Layers {
+    stack: Arc<RwLock<Stack>>,
+    dispatched: Stream<(Vec<u64>, Timed<Event>)>
+}
+
+impl Layers {
+    pub fn new(events: &Stream<Timed<Event>>) -> Layers {
+        let stack = Arc::new(RwLock::new(Stack::default()));
+        let dispatch_stack = stack.clone();
+        let dispatched = events.map(move |event| {
+            let receivers = dispatch_stack.write().unwrap().receivers(&event.value);
+            (receivers, event)
+        });
+        Layers { stack: stack, dispatched: dispatched }
+    }
+
+    /// Add a layer that swallows the events `swallow` returns true for.
+    ///
+    /// Among layers of equal priority, the one added later is on top.
+    pub fn add<F>(&self, priority: i32, swallow: F) -> LayerView
+        where F: Fn(&Event) -> bool + Send + Sync + 'static
+    {
+        let mut stack = self.stack.write().unwrap();
+        let id = stack.next_id;
+        stack.next_id += 1;
+        let index = stack.layers.iter()
+            .position(|layer| layer.priority <= priority)
+            .unwrap_or(stack.layers.len());
+        let layer = Layer { id: id, priority: priority, swallow: Box::new(swallow) };
+        stack.layers.insert(index, layer);
+        LayerView { id: id, dispatched: self.dispatched.clone() }
+    }
+
+    /// Add a layer that swallows all events while `active`, like a modal
+    /// dialog.
+    pub fn add_modal(&self, priority: i32, active: Signal<bool>) -> LayerView {
+        self.add(priority, move |_| active.sample())
+    }
+
+    /// Remove a layer. Its view stops receiving events.
+    pub fn remove(&self, view: &LayerView) {
+        self.stack.write().unwrap().layers.retain(|layer| layer.id != view.id);
+    }
+}
+
+/// The events that reach one layer.
+///
+/// Only `Event`s are layered. Cursor motion and the wheel are not part of
+/// them, so route those by position with a `Router` instead.
+#[derive(Clone)]
+pub struct LayerView {
+    id: u64,
+    dispatched: Stream<(Vec<u64>, Timed<Event>)>
+}
+
+impl LayerView {
+    pub fn events(&self) -> Stream<Event> {
+        self.timed_events().map(|event| event.value)
+    }
+
+    pub fn timed_events(&self) -> Stream<Timed<Event>> {
+        let id = self.id;
+        self.dispatched
+            .filter_map(move |(receivers, event)|
+                if receivers.contains(&id) { Some(event) } else { None })
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use carboxyl::Sink;
+    use glutin::VirtualKeyCode::A;
+    use glutin::MouseButton::Left;
+    use ::{Button, Event, Timed};
+    use testing::collect;
+    use super::Layers;
+
+    #[test]
+    fn higher_layers_swallow_events() {
+        let events = Sink::new();
+        let active = Sink::new();
+        let layers = Layers::new(&events.stream());
+        let game = layers.add(0, |_| false);
+        let console = layers.add(10, |event|
+            matches!(*event, Event::Press(Button::Keyboard(_))));
+        let modal = layers.add_modal(20, active.stream().hold(false));
+        let count = |view: &super::LayerView| view.events().fold(0, |n, _| n + 1);
+        let (game, console, modal) = (count(&game), count(&console), count(&modal));
+        let send = |event| events.send(Timed::new(0, 0, event));
+        send(Event::Press(Button::Keyboard(A)));
+        send(Event::Press(Button::Mouse(Left)));
+        active.send(true);
+        send(Event::Press(Button::Mouse(Left)));
+        assert_eq!((game.sample(), console.sample(), modal.sample()), (1, 2, 3));
+    }
+
+    #[test]
+    fn releases_reach_every_layer_that_saw_the_press() {
+        let events = Sink::new();
+        let active = Sink::new();
+        let layers = Layers::new(&events.stream());
+        let game = layers.add(0, |_| false);
+        let modal = layers.add_modal(20, active.stream().hold(false));
+        let (game, modal) = (collect(&game.events()), collect(&modal.events()));
+        let send = |event| events.send(Timed::new(0, 0, event));
+        send(Event::Press(Button::Keyboard(A)));
+        active.send(true);
+        send(Event::Release(Button::Keyboard(A)));
+        let a = Button::Keyboard(A);
+        assert_eq!(game.sample(), vec![Event::Press(a), Event::Release(a)]);
+        assert_eq!(modal.sample(), vec![Event::Press(a), Event::Release(a)]);
+    }
+}
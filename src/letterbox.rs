@@ -0,0 +1,84 @@
+use carboxyl::Signal;
+use glutin;
+use middleware::{Middleware, CursorTransform};
+use region::Rect;
+
+/// The largest rectangle of the given aspect ratio (width over height) that
+/// fits into a window of `size`, centered with bars on the remaining sides.
+///
+/// Panics unless `aspect` is positive and finite.
+pub fn letterbox(size: (u32, u32), aspect: f64) -> Rect {
+    assert!(aspect.is_finite() && aspect > 0.0);
+    let (width, height) = (size.0 as f64, size.1 as f64);
+    if height == 0.0 || width / height > aspect {
+        let inner = height * aspect;
+        Rect::new((width - inner) / 2.0, 0.0, inner, height)
+    } else {
+        let inner = width / aspect;
+        Rect::new(0.0, (height - inner) / 2.0, width, inner)
+    }
+}
+
+/// Maps cursor and touch positions into a letterboxed viewport.
+///
+/// Positions become relative to the viewport's top left corner and, with
+/// `scaled`, are scaled to a fixed resolution. Positions on the bars fall
+/// outside of the viewport and are passed on as such.
+pub struct Letterbox {
+    viewport: Signal<Rect>,
+    resolution: Option<(f64, f64)>
+}
+
+impl Letterbox {
+    pub fn new(viewport: Signal<Rect>) -> Letterbox {
+        Letterbox { viewport: viewport, resolution: None }
+    }
+
+    /// Scale positions so that the viewport spans `resolution`.
+    pub fn scaled(viewport: Signal<Rect>, resolution: (f64, f64)) -> Letterbox {
+        Letterbox { viewport: viewport, resolution: Some(resolution) }
+    }
+
+    fn transform(&self) -> CursorTransform {
+        let viewport = self.viewport.sample();
+        let scale = match self.resolution {
+            Some((x, y)) if viewport.width > 0.0 && viewport.height > 0.0 =>
+                (x / viewport.width, y / viewport.height),
+            _ => (1.0, 1.0)
+        };
+        CursorTransform::new((viewport.x, viewport.y), scale)
+    }
+}
+
+impl Middleware for Letterbox {
+    fn process(&mut self, event: glutin::Event) -> Option<glutin::Event> {
+        self.transform().process(event)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use carboxyl::Signal;
+    use glutin::Event::MouseMoved;
+    use middleware::Middleware;
+    use region::Rect;
+    use super::{letterbox, Letterbox};
+
+    #[test]
+    fn adds_bars_on_the_longer_side() {
+        assert_eq!(letterbox((400, 100), 2.0), Rect::new(100.0, 0.0, 200.0, 100.0));
+        assert_eq!(letterbox((100, 100), 2.0), Rect::new(0.0, 25.0, 100.0, 50.0));
+        assert_eq!(letterbox((0, 0), 2.0), Rect::new(0.0, 0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn maps_the_cursor_into_the_viewport() {
+        let viewport = Signal::new(Rect::new(100.0, 0.0, 200.0, 100.0));
+        let mut scaled = Letterbox::scaled(viewport, (400.0, 200.0));
+        match scaled.process(MouseMoved(150, 50)) {
+            Some(MouseMoved(100, 100)) => (),
+            event => panic!("unexpected {:?}", event)
+        }
+    }
+}
@@ -1,10 +1,87 @@
 extern crate carboxyl;
 extern crate clock_ticks;
 extern crate glutin;
+#[cfg(feature = "ctrlc")]
+extern crate ctrlc;
+#[cfg(feature = "quickcheck")]
+extern crate quickcheck;
+#[cfg(feature = "tracing")]
+extern crate tracing;
+#[cfg(feature = "profiling")]
+extern crate puffin;
+#[cfg(feature = "unicode-segmentation")]
+extern crate unicode_segmentation;
 
 pub use driver::WindowDriver;
-pub use core::{Event, Cursor, WindowProperties, Context};
+pub use source::EventSource;
+pub use queue::{Overflow, QueueSender, QueueSource, bounded_queue, bounded_queue_with};
+pub use drop_policy::{DropPolicy, EventCategory};
+pub use spsc::{SpscProducer, SpscSource, spsc_queue};
+pub use fuzz::{FuzzSource, FuzzConfig};
+pub use idle::IdlePolicy;
+pub use key_repeat::KeyRepeat;
+pub use close::CloseHandle;
+pub use custom::CustomSink;
+pub use middleware::{Middleware, CursorTransform, KeyRemap, WheelScale};
+pub use layers::{Layers, LayerView, Swallow};
+pub use letterbox::{Letterbox, letterbox};
+pub use resize_edge::ResizeEdge;
+pub use title_bar::{TitleBar, TitleBarLayout};
+pub use latency::{LatencyStats, latency_probe};
+pub use replay::{Dispatched, sequence_hash};
+pub use metrics::Metrics;
+pub use region::{Rect, Region};
+pub use router::{Router, PointerEvent, PointerAction, Phase, Handler, Dispatch,
+                 topmost};
+pub use focus::{FocusManager, FocusChange, TabOrder};
+pub use text_field::{TextField, TextEdit, TextFieldModel, text_field};
+pub use undo::{Command, History, HistoryUpdate, UndoModel, undo_history};
+#[cfg(feature = "gestures")]
+pub use gesture::{Gesture, GestureRecognizer, GestureRegistry, TapRecognizer,
+                  DragRecognizer};
+pub use scheduler::{Scheduler, FixedRate, VSync};
+pub use core::{Event, Button, ButtonEvent, ButtonState, Timed, ScrollDelta, Touch,
+               Cursor, WindowProperties, LockKeys, Context, FramePhase, Profiler,
+               LateFrame, PanicPolicy, Modifiers};
 
 mod driver;
+mod source;
+mod queue;
+mod drop_policy;
+mod spsc;
+mod fuzz;
+pub mod backend;
+pub mod testing;
+pub mod time_combinators;
+pub mod anim;
+pub mod easing;
+#[cfg(feature = "quickcheck")]
+mod arbitrary;
+mod clicks;
+mod momentum;
+mod debounce;
+mod hold;
+mod idle;
+mod key_repeat;
+mod compose;
+mod scheduler;
+mod close;
+mod custom;
+mod middleware;
+mod layers;
+mod letterbox;
+mod resize_edge;
+mod title_bar;
+mod constraints;
+mod latency;
+mod metrics;
+mod region;
+mod router;
+mod focus;
+mod text_field;
+mod undo;
+#[cfg(feature = "gestures")]
+mod gesture;
+mod replay;
 mod updates;
 mod core;
@@ -0,0 +1,87 @@
+use std::collections::BTreeMap;
+
+/// Length of a metrics window in nanoseconds.
+const WINDOW: u64 = 1_000_000_000;
+
+/// Run loop statistics over the last complete second.
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub struct Metrics {
+    /// Number of backend events of each kind.
+    pub events: BTreeMap<&'static str, u32>,
+    /// Number of rendered frames.
+    pub frames: u32,
+    /// Total time spent dispatching, in nanoseconds.
+    pub dispatch: u64,
+    /// Total time spent rendering, in nanoseconds.
+    pub render: u64
+}
+
+impl Metrics {
+    /// Total number of events of all kinds.
+    pub fn total_events(&self) -> u32 {
+        self.events.values().sum()
+    }
+}
+
+/// Accumulates metrics and cuts them into one second windows.
+pub struct Collector {
+    current: Metrics,
+    start: Option<u64>
+}
+
+impl Collector {
+    pub fn new() -> Collector {
+        Collector { current: Metrics::default(), start: None }
+    }
+
+    pub fn count(&mut self, kind: &'static str) {
+        *self.current.events.entry(kind).or_insert(0) += 1;
+    }
+
+    pub fn frame(&mut self, dispatch: u64, render: u64) {
+        self.current.frames += 1;
+        self.current.dispatch += dispatch;
+        self.current.render += render;
+    }
+
+    /// Finish the current window if a second has passed since it started.
+    pub fn roll(&mut self, now: u64) -> Option<Metrics> {
+        let start = *self.start.get_or_insert(now);
+        if now.saturating_sub(start) < WINDOW {
+            return None;
+        }
+        self.start = Some(now);
+        Some(::std::mem::take(&mut self.current))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rolls_over_after_a_second() {
+        let mut collector = Collector::new();
+        assert_eq!(collector.roll(0), None);
+        collector.count("mouse_moved");
+        collector.count("mouse_moved");
+        collector.count("closed");
+        collector.frame(10, 20);
+        assert_eq!(collector.roll(WINDOW - 1), None);
+        let metrics = collector.roll(WINDOW).unwrap();
+        assert_eq!(metrics.events["mouse_moved"], 2);
+        assert_eq!(metrics.total_events(), 3);
+        assert_eq!((metrics.frames, metrics.dispatch, metrics.render), (1, 10, 20));
+        assert_eq!(collector.roll(WINDOW + 1), None);
+    }
+
+    #[test]
+    fn starts_over_empty() {
+        let mut collector = Collector::new();
+        collector.roll(0);
+        collector.count("refresh");
+        collector.roll(WINDOW);
+        assert_eq!(collector.roll(2 * WINDOW), Some(Metrics::default()));
+    }
+}
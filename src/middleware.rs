@@ -0,0 +1,133 @@
+use std::collections::HashMap;
+use glutin::{self, VirtualKeyCode};
+
+/// Rewrites backend events before the driver dispatches them.
+///
+/// Returning `None` drops the event. Middleware runs in a chain, each one
+/// seeing the output of the one before.
+pub trait Middleware {
+    fn process(&mut self, event: glutin::Event) -> Option<glutin::Event>;
+}
+
+impl<F: FnMut(glutin::Event) -> Option<glutin::Event>> Middleware for F {
+    fn process(&mut self, event: glutin::Event) -> Option<glutin::Event> {
+        self(event)
+    }
+}
+
+/// Maps cursor and touch positions from window to viewport coordinates,
+/// e.g. for a letterboxed viewport: `viewport = (window - offset) * scale`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CursorTransform {
+    pub offset: (f64, f64),
+    pub scale: (f64, f64)
+}
+
+impl CursorTransform {
+    pub fn new(offset: (f64, f64), scale: (f64, f64)) -> CursorTransform {
+        CursorTransform { offset: offset, scale: scale }
+    }
+
+    pub fn apply(&self, point: (f64, f64)) -> (f64, f64) {
+        ((point.0 - self.offset.0) * self.scale.0,
+         (point.1 - self.offset.1) * self.scale.1)
+    }
+}
+
+impl Middleware for CursorTransform {
+    fn process(&mut self, event: glutin::Event) -> Option<glutin::Event> {
+        Some(match event {
+            glutin::Event::MouseMoved(x, y) => {
+                let (x, y) = self.apply((x as f64, y as f64));
+                glutin::Event::MouseMoved(x.round() as i32, y.round() as i32)
+            },
+            glutin::Event::Touch(touch) => glutin::Event::Touch(glutin::Touch {
+                location: self.apply(touch.location),
+                .. touch
+            }),
+            event => event
+        })
+    }
+}
+
+/// Replaces keys by others, e.g. for user-configurable bindings.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct KeyRemap {
+    pub keys: HashMap<VirtualKeyCode, VirtualKeyCode>
+}
+
+impl KeyRemap {
+    pub fn new() -> KeyRemap {
+        KeyRemap::default()
+    }
+
+    pub fn map(mut self, from: VirtualKeyCode, to: VirtualKeyCode) -> KeyRemap {
+        self.keys.insert(from, to);
+        self
+    }
+}
+
+impl Middleware for KeyRemap {
+    fn process(&mut self, event: glutin::Event) -> Option<glutin::Event> {
+        Some(match event {
+            glutin::Event::KeyboardInput(state, code, Some(key)) => {
+                let key = self.keys.get(&key).cloned().unwrap_or(key);
+                glutin::Event::KeyboardInput(state, code, Some(key))
+            },
+            event => event
+        })
+    }
+}
+
+/// Scales wheel deltas, e.g. for a scroll sensitivity setting.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WheelScale(pub f32);
+
+impl Middleware for WheelScale {
+    fn process(&mut self, event: glutin::Event) -> Option<glutin::Event> {
+        use glutin::MouseScrollDelta::{LineDelta, PixelDelta};
+        Some(match event {
+            glutin::Event::MouseWheel(delta, phase) => {
+                let delta = match delta {
+                    LineDelta(x, y) => LineDelta(x * self.0, y * self.0),
+                    PixelDelta(x, y) => PixelDelta(x * self.0, y * self.0)
+                };
+                glutin::Event::MouseWheel(delta, phase)
+            },
+            event => event
+        })
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use glutin::Event::{KeyboardInput, MouseMoved, MouseWheel};
+    use glutin::ElementState::Pressed;
+    use glutin::MouseScrollDelta::LineDelta;
+    use glutin::TouchPhase::Moved;
+    use glutin::VirtualKeyCode::{A, Space};
+    use super::*;
+
+    #[test]
+    fn transforms_cursor_positions() {
+        let mut transform = CursorTransform::new((10.0, 20.0), (0.5, 2.0));
+        match transform.process(MouseMoved(30, 25)) {
+            Some(MouseMoved(10, 10)) => (),
+            other => panic!("unexpected {:?}", other)
+        }
+    }
+
+    #[test]
+    fn remaps_keys_and_scales_the_wheel() {
+        let mut remap = KeyRemap::new().map(Space, A);
+        match remap.process(KeyboardInput(Pressed, 57, Some(Space))) {
+            Some(KeyboardInput(Pressed, 57, Some(A))) => (),
+            other => panic!("unexpected {:?}", other)
+        }
+        match WheelScale(3.0).process(MouseWheel(LineDelta(0.0, 1.0), Moved)) {
+            Some(MouseWheel(LineDelta(x, y), _)) => assert_eq!((x, y), (0.0, 3.0)),
+            other => panic!("unexpected {:?}", other)
+        }
+    }
+}
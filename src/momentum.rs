@@ -0,0 +1,77 @@
+/// Kinetic scrolling state.
+///
+/// Wheel deltas add to a velocity that decays exponentially at `decay` per
+/// second, so that the position eventually travels exactly the accumulated
+/// wheel distance, only spread out over time.
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub struct Momentum {
+    pub position: (f64, f64),
+    pub velocity: (f64, f64),
+    pub time: Option<u64>
+}
+
+#[derive(Clone)]
+pub enum MomentumUpdate {
+    Impulse(f64, f64),
+    Advance(u64)
+}
+
+impl MomentumUpdate {
+    pub fn apply(self, current: Momentum, decay: f64) -> Momentum {
+        use self::MomentumUpdate::*;
+        match self {
+            Impulse(dx, dy) => Momentum {
+                velocity: (
+                    current.velocity.0 + decay * dx,
+                    current.velocity.1 + decay * dy
+                ),
+                .. current
+            },
+            Advance(time) => {
+                let dt = match current.time {
+                    Some(last) => time.saturating_sub(last) as f64 * 1e-9,
+                    None => 0.0
+                };
+                let factor = (-decay * dt).exp();
+                let travel = (1.0 - factor) / decay;
+                Momentum {
+                    position: (
+                        current.position.0 + current.velocity.0 * travel,
+                        current.position.1 + current.velocity.1 * travel
+                    ),
+                    velocity: (
+                        current.velocity.0 * factor,
+                        current.velocity.1 * factor
+                    ),
+                    time: Some(time)
+                }
+            }
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::{Momentum, MomentumUpdate};
+    use super::MomentumUpdate::*;
+
+    fn run(updates: Vec<MomentumUpdate>) -> Momentum {
+        updates.into_iter()
+            .fold(Momentum::default(), |state, update| update.apply(state, 10.0))
+    }
+
+    #[test]
+    fn glides_towards_the_accumulated_wheel_distance() {
+        let state = run(vec![Advance(0), Impulse(0.0, 3.0), Advance(50_000_000)]);
+        assert!(state.position.1 > 0.0 && state.position.1 < 3.0);
+        let state = run(vec![Advance(0), Impulse(0.0, 3.0), Advance(5_000_000_000)]);
+        assert!((state.position.1 - 3.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn does_not_move_without_elapsed_time() {
+        let state = run(vec![Impulse(2.0, 0.0), Advance(1_000)]);
+        assert_eq!(state.position, (0.0, 0.0));
+    }
+}
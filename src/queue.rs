@@ -0,0 +1,274 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, Condvar};
+use glutin;
+use source::EventSource;
+use drop_policy::{DropPolicy, EventCategory};
+
+/// What a bounded queue does with an event that arrives while it is full.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Overflow {
+    /// Wait until the driver has polled.
+    Block,
+    /// Drop the oldest queued event that the drop policy allows to lose and
+    /// that a later event of the same kind supersedes.
+    DropOldest,
+    /// Merge the event into the newest queued one if they are of a kind
+    /// that can be merged, such as two cursor motions. Otherwise drop as
+    /// with `DropOldest`.
+    Coalesce
+}
+
+/// Queue state shared between the producer and the driver.
+struct Bounded {
+    events: VecDeque<glutin::Event>,
+    capacity: usize,
+    overflow: Overflow,
+    policy: DropPolicy,
+    /// Set once the source is dropped, after which nothing is queued.
+    closed: bool
+}
+
+impl Bounded {
+    /// Add an event unless the queue is full and the policy is to block, in
+    /// which case the event is handed back.
+    fn push(&mut self, event: glutin::Event) -> Result<(), glutin::Event> {
+        if self.events.len() < self.capacity {
+            self.events.push_back(event);
+            return Ok(());
+        }
+        let policy = &self.policy;
+        match self.overflow {
+            Overflow::Block => return Err(event),
+            Overflow::Coalesce if self.events.back_mut()
+                .is_some_and(|last| policy.coalesce(last, &event)) => (),
+            Overflow::DropOldest | Overflow::Coalesce => {
+                match self.superseded(&event) {
+                    Some(index) => {
+                        self.events.remove(index);
+                        self.events.push_back(event);
+                    },
+                    // Nothing queued may be lost, so lose the new event if it
+                    // may be, or exceed the capacity
+                    None if policy.may_drop(&event) => (),
+                    None => self.events.push_back(event)
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Index of the oldest queued event that may be dropped because a later
+    /// one of the same kind, possibly `event`, supersedes it.
+    ///
+    /// A button or wheel event in between depends on the position of the
+    /// earlier one, e.g. a press on the last motion before it, so nothing
+    /// before it is superseded across.
+    fn superseded(&self, event: &glutin::Event) -> Option<usize> {
+        let later = |index: usize| self.events.iter().skip(index + 1)
+            .chain(Some(event))
+            .map(EventCategory::of);
+        self.events.iter().enumerate()
+            .filter(|&(_, queued)| self.policy.may_drop(queued))
+            .find(|&(index, queued)| {
+                let category = EventCategory::of(queued);
+                later(index)
+                    .take_while(|&next| next == category || !next.is_positional())
+                    .any(|next| next == category)
+            })
+            .map(|(index, _)| index)
+    }
+}
+
+type Shared = Arc<(Mutex<Bounded>, Condvar)>;
+
+/// Sends events from a background thread into a bounded queue.
+#[derive(Clone)]
+pub struct QueueSender {
+    shared: Shared
+}
+
+impl QueueSender {
+    /// Queue an event, applying the overflow policy if the queue is full.
+    ///
+    /// The event is handed back if the source has been dropped, including
+    /// while waiting for it to poll.
+    pub fn send(&self, event: glutin::Event) -> Result<(), glutin::Event> {
+        let (ref lock, ref polled) = *self.shared;
+        let mut queue = lock.lock().unwrap();
+        let mut event = event;
+        loop {
+            if queue.closed {
+                return Err(event);
+            }
+            match queue.push(event) {
+                Ok(()) => return Ok(()),
+                Err(blocked) => event = blocked
+            }
+            queue = polled.wait(queue).unwrap();
+        }
+    }
+}
+
+/// The driver's end of a bounded queue.
+pub struct QueueSource {
+    shared: Shared
+}
+
+impl EventSource for QueueSource {
+    fn poll_events(&mut self) -> Vec<glutin::Event> {
+        let (ref lock, ref polled) = *self.shared;
+        let events = lock.lock().unwrap().events.drain(..).collect();
+        polled.notify_all();
+        events
+    }
+}
+
+impl Drop for QueueSource {
+    fn drop(&mut self) {
+        let (ref lock, ref polled) = *self.shared;
+        lock.lock().unwrap().closed = true;
+        polled.notify_all();
+    }
+}
+
+/// A queue of at most `capacity` events between a polling thread and the
+/// driver.
+///
+/// This bounds the backlog that builds up while the driver is stalled, e.g.
+/// by a long render, and thereby the latency of the events after it. Events
+/// are dropped according to the default `DropPolicy`.
+pub fn bounded_queue(capacity: usize, overflow: Overflow)
+    -> (QueueSender, QueueSource)
+{
+    bounded_queue_with(capacity, overflow, DropPolicy::default())
+}
+
+/// A bounded queue that only drops events `policy` allows to lose.
+///
+/// Events the policy does not allow to drop are queued even beyond the
+/// capacity, rather than being lost.
+pub fn bounded_queue_with(capacity: usize, overflow: Overflow, policy: DropPolicy)
+    -> (QueueSender, QueueSource)
+{
+    assert!(capacity > 0);
+    let bounded = Bounded {
+        events: VecDeque::with_capacity(capacity),
+        capacity: capacity,
+        overflow: overflow,
+        policy: policy,
+        closed: false
+    };
+    let shared = Arc::new((Mutex::new(bounded), Condvar::new()));
+    (QueueSender { shared: shared.clone() }, QueueSource { shared: shared })
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+    use glutin::Event::{MouseMoved, ReceivedCharacter, MouseWheel, Resized};
+    use glutin::MouseScrollDelta::LineDelta;
+    use glutin::TouchPhase::Moved;
+    use source::EventSource;
+    use super::*;
+
+    fn debug(events: Vec<glutin::Event>) -> Vec<String> {
+        events.iter().map(|event| format!("{:?}", event)).collect()
+    }
+
+    #[test]
+    fn drops_the_oldest_droppable_event() {
+        let (sender, mut source) = bounded_queue(2, Overflow::DropOldest);
+        sender.send(ReceivedCharacter('a')).unwrap();
+        sender.send(MouseMoved(1, 1)).unwrap();
+        sender.send(MouseMoved(2, 2)).unwrap();
+        assert_eq!(debug(source.poll_events()),
+                   debug(vec![ReceivedCharacter('a'), MouseMoved(2, 2)]));
+    }
+
+    #[test]
+    fn keeps_the_motion_a_press_happened_at() {
+        use glutin::ElementState::Pressed;
+        use glutin::Event::MouseInput;
+        use glutin::MouseButton::Left;
+        let (sender, mut source) = bounded_queue(3, Overflow::DropOldest);
+        sender.send(MouseMoved(1, 1)).unwrap();
+        sender.send(MouseInput(Pressed, Left)).unwrap();
+        sender.send(MouseMoved(2, 2)).unwrap();
+        sender.send(MouseMoved(3, 3)).unwrap();
+        sender.send(MouseMoved(4, 4)).unwrap();
+        assert_eq!(debug(source.poll_events()), debug(vec![
+            MouseMoved(1, 1), MouseInput(Pressed, Left), MouseMoved(4, 4)
+        ]));
+    }
+
+    #[test]
+    fn never_drops_geometry() {
+        let (sender, mut source) = bounded_queue(2, Overflow::DropOldest);
+        sender.send(Resized(5, 5)).unwrap();
+        sender.send(MouseMoved(1, 1)).unwrap();
+        sender.send(MouseMoved(2, 2)).unwrap();
+        sender.send(MouseMoved(3, 3)).unwrap();
+        assert_eq!(debug(source.poll_events()),
+                   debug(vec![Resized(5, 5), MouseMoved(3, 3)]));
+        let (sender, mut source) = bounded_queue(1, Overflow::Coalesce);
+        sender.send(Resized(5, 5)).unwrap();
+        sender.send(ReceivedCharacter('a')).unwrap();
+        sender.send(MouseMoved(1, 1)).unwrap();
+        assert_eq!(debug(source.poll_events()),
+                   debug(vec![Resized(5, 5), ReceivedCharacter('a')]));
+    }
+
+    #[test]
+    fn never_drops_critical_events() {
+        let (sender, mut source) = bounded_queue(1, Overflow::DropOldest);
+        sender.send(ReceivedCharacter('a')).unwrap();
+        sender.send(MouseMoved(1, 1)).unwrap();
+        sender.send(ReceivedCharacter('b')).unwrap();
+        assert_eq!(debug(source.poll_events()),
+                   debug(vec![ReceivedCharacter('a'), ReceivedCharacter('b')]));
+    }
+
+    #[test]
+    fn coalesces_wheel_deltas() {
+        let (sender, mut source) = bounded_queue(2, Overflow::Coalesce);
+        sender.send(ReceivedCharacter('a')).unwrap();
+        sender.send(MouseWheel(LineDelta(0.0, 1.0), Moved)).unwrap();
+        sender.send(MouseWheel(LineDelta(0.0, 2.0), Moved)).unwrap();
+        sender.send(MouseMoved(1, 1)).unwrap();
+        let expected = vec![
+            ReceivedCharacter('a'),
+            MouseWheel(LineDelta(0.0, 3.0), Moved)
+        ];
+        assert_eq!(debug(source.poll_events()), debug(expected));
+    }
+
+    #[test]
+    fn stops_blocking_when_the_source_is_dropped() {
+        let (sender, source) = bounded_queue(1, Overflow::Block);
+        sender.send(ReceivedCharacter('a')).unwrap();
+        let producer = thread::spawn(move || sender.send(ReceivedCharacter('b')));
+        thread::sleep(::std::time::Duration::from_millis(10));
+        drop(source);
+        assert!(producer.join().unwrap().is_err());
+    }
+
+    #[test]
+    fn blocks_until_polled() {
+        let (sender, mut source) = bounded_queue(1, Overflow::Block);
+        let producer = thread::spawn(move || {
+            for c in "abc".chars() {
+                sender.send(ReceivedCharacter(c)).unwrap();
+            }
+        });
+        let mut received = vec![];
+        while received.len() < 3 {
+            received.extend(source.poll_events());
+            thread::yield_now();
+        }
+        producer.join().unwrap();
+        assert_eq!(debug(received), debug(vec![
+            ReceivedCharacter('a'), ReceivedCharacter('b'), ReceivedCharacter('c')
+        ]));
+    }
+}
@@ -0,0 +1,53 @@
+use carboxyl::{Signal, Stream};
+use glutin::MouseButton;
+
+/// An axis-aligned rectangle in window coordinates.
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub struct Rect {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64
+}
+
+impl Rect {
+    pub fn new(x: f64, y: f64, width: f64, height: f64) -> Rect {
+        Rect { x: x, y: y, width: width, height: height }
+    }
+
+    /// Whether `point` lies inside, including the top and left edges but not
+    /// the bottom and right ones, so that adjacent rectangles do not overlap.
+    pub fn contains(&self, point: (f64, f64)) -> bool {
+        point.0 >= self.x && point.0 < self.x + self.width &&
+        point.1 >= self.y && point.1 < self.y + self.height
+    }
+}
+
+/// Pointer events scoped to a rectangular region of the window.
+#[derive(Clone)]
+pub struct Region {
+    /// Whether the cursor is currently inside.
+    pub hovered: Signal<bool>,
+    /// Fires with the cursor position when the cursor enters.
+    pub enter: Stream<(f64, f64)>,
+    /// Fires with the cursor position when the cursor leaves.
+    pub leave: Stream<(f64, f64)>,
+    /// Clicks inside, with their click count.
+    pub clicks: Stream<(MouseButton, u32)>
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::Rect;
+
+    #[test]
+    fn contains_its_top_left_but_not_bottom_right_edge() {
+        let rect = Rect::new(10.0, 20.0, 5.0, 5.0);
+        assert!(rect.contains((10.0, 20.0)));
+        assert!(rect.contains((14.9, 24.9)));
+        assert!(!rect.contains((15.0, 22.0)));
+        assert!(!rect.contains((12.0, 25.0)));
+        assert!(!rect.contains((9.9, 22.0)));
+    }
+}
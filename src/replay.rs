@@ -0,0 +1,221 @@
+use carboxyl::{Signal, Stream};
+use glutin::MouseButton;
+use ::{Button, Event, ScrollDelta, Timed, Touch};
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// 64-bit FNV-1a over an explicit little-endian encoding.
+///
+/// Unlike `DefaultHasher`, the output is fixed by the algorithm and does not
+/// change between Rust releases, so stored hashes stay comparable.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Fnv(u64);
+
+impl Fnv {
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0 ^ byte as u64).wrapping_mul(FNV_PRIME);
+        }
+    }
+
+    fn write_u8(&mut self, value: u8) {
+        self.write(&[value]);
+    }
+
+    fn write_u32(&mut self, value: u32) {
+        self.write(&value.to_le_bytes());
+    }
+
+    fn write_u64(&mut self, value: u64) {
+        self.write(&value.to_le_bytes());
+    }
+
+    fn write_f64(&mut self, value: f64) {
+        self.write_u64(value.to_bits());
+    }
+
+    fn write_pair(&mut self, (x, y): (f64, f64)) {
+        self.write_f64(x);
+        self.write_f64(y);
+    }
+
+    fn write_option(&mut self, value: Option<f64>) {
+        match value {
+            None => self.write_u8(0),
+            Some(value) => {
+                self.write_u8(1);
+                self.write_f64(value);
+            }
+        }
+    }
+
+    fn write_mouse(&mut self, button: MouseButton) {
+        match button {
+            MouseButton::Left => self.write_u8(0),
+            MouseButton::Right => self.write_u8(1),
+            MouseButton::Middle => self.write_u8(2),
+            MouseButton::Other(other) => {
+                self.write_u8(3);
+                self.write_u8(other);
+            }
+        }
+    }
+
+    /// Keys are written as their position in glutin's `VirtualKeyCode`.
+    fn write_button(&mut self, button: Button) {
+        match button {
+            Button::Keyboard(key) => {
+                self.write_u8(0);
+                self.write_u32(key as u32);
+            },
+            Button::Mouse(button) => {
+                self.write_u8(1);
+                self.write_mouse(button);
+            }
+        }
+    }
+
+    fn write_event(&mut self, event: &Event) {
+        match *event {
+            Event::Press(button) => {
+                self.write_u8(0);
+                self.write_button(button);
+            },
+            Event::Release(button) => {
+                self.write_u8(1);
+                self.write_button(button);
+            },
+            Event::Click(button, count) => {
+                self.write_u8(2);
+                self.write_mouse(button);
+                self.write_u32(count);
+            },
+            Event::Text(ref text) => {
+                self.write_u8(3);
+                self.write_u64(text.len() as u64);
+                self.write(text.as_bytes());
+            },
+            Event::Repeat(button) => {
+                self.write_u8(4);
+                self.write_button(button);
+            },
+            Event::Suspend => self.write_u8(5),
+            Event::Resume => self.write_u8(6),
+            Event::Shutdown => self.write_u8(7)
+        }
+    }
+
+    fn write_dispatched(&mut self, dispatched: &Dispatched) {
+        match *dispatched {
+            Dispatched::Event(ref event) => {
+                self.write_u8(0);
+                self.write_event(event);
+            },
+            Dispatched::Motion(position) => {
+                self.write_u8(1);
+                self.write_pair(position);
+            },
+            Dispatched::Wheel(ScrollDelta::Lines(x, y)) => {
+                self.write_u8(2);
+                self.write_u8(0);
+                self.write_pair((x, y));
+            },
+            Dispatched::Wheel(ScrollDelta::Pixels(x, y)) => {
+                self.write_u8(2);
+                self.write_u8(1);
+                self.write_pair((x, y));
+            },
+            Dispatched::Resize((width, height)) => {
+                self.write_u8(3);
+                self.write_u32(width);
+                self.write_u32(height);
+            },
+            Dispatched::Touch(touch) => {
+                self.write_u8(4);
+                self.write_u64(touch.id);
+                self.write_u8(touch.phase as u8);
+                self.write_pair(touch.position);
+                self.write_option(touch.pressure);
+                self.write_option(touch.radius);
+            }
+        }
+    }
+}
+
+/// A value the driver dispatched, from any of its timed streams.
+///
+/// Derived values, such as button events, text and gestures, follow from
+/// these and are left out.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Dispatched {
+    Event(Event),
+    Motion((f64, f64)),
+    Wheel(ScrollDelta),
+    Resize((u32, u32)),
+    Touch(Touch)
+}
+
+/// Combine a dispatched value into a running sequence hash.
+pub fn chain_hash(previous: u64, dispatched: &Timed<Dispatched>, include_time: bool)
+    -> u64
+{
+    let mut hasher = Fnv(previous);
+    hasher.write_u64(dispatched.seq);
+    if include_time {
+        hasher.write_u64(dispatched.time);
+    }
+    hasher.write_dispatched(&dispatched.value);
+    hasher.0
+}
+
+/// A running hash of a dispatched sequence, such as
+/// `WindowDriver::dispatched`, for checking that two runs saw exactly the
+/// same sequence.
+///
+/// The hash is 64-bit FNV-1a over a fixed encoding of the values, so it can
+/// be stored as a regression reference. Timestamps only match between runs
+/// if events are fed with deterministic times, so they can be left out.
+pub fn sequence_hash(dispatched: &Stream<Timed<Dispatched>>, include_time: bool)
+    -> Signal<u64>
+{
+    dispatched.fold(FNV_OFFSET, move |hash, value| chain_hash(hash, &value, include_time))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use carboxyl::Sink;
+    use ::{Event, Timed};
+    use super::{Dispatched, sequence_hash};
+
+    fn run(values: Vec<Timed<Dispatched>>, include_time: bool) -> u64 {
+        let sink = Sink::new();
+        let hash = sequence_hash(&sink.stream(), include_time);
+        sink.feed(values);
+        hash.sample()
+    }
+
+    fn text(time: u64, seq: u64, text: &str) -> Timed<Dispatched> {
+        Timed::new(time, seq, Dispatched::Event(Event::Text(text.to_string())))
+    }
+
+    #[test]
+    fn hashes_are_fixed_across_builds() {
+        let values = vec![text(10, 1, "a"), Timed::new(20, 2, Dispatched::Resize((3, 4)))];
+        assert_eq!(run(values, true), 0x3a41_9828_8d58_424b);
+    }
+
+    #[test]
+    fn order_matters() {
+        let (a, b) = (text(0, 1, "a"), text(0, 1, "b"));
+        assert!(run(vec![a.clone(), b.clone()], false) != run(vec![b, a], false));
+    }
+
+    #[test]
+    fn can_ignore_timestamps() {
+        let at = |time| vec![Timed::new(time, 1, Dispatched::Event(Event::Suspend))];
+        assert_eq!(run(at(5), false), run(at(7), false));
+        assert!(run(at(5), true) != run(at(7), true));
+    }
+}
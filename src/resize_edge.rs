@@ -0,0 +1,97 @@
+/// An edge or corner of the window that resizing can start from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ResizeEdge {
+    North,
+    South,
+    East,
+    West,
+    NorthEast,
+    NorthWest,
+    SouthEast,
+    SouthWest
+}
+
+impl ResizeEdge {
+    /// The edge that `point` lies on, where edges are `border` pixels wide
+    /// bands along the inside of a window of `size`. Corners take precedence.
+    pub fn at(point: (f64, f64), size: (u32, u32), border: f64) -> Option<ResizeEdge> {
+        use self::ResizeEdge::*;
+        let (width, height) = (size.0 as f64, size.1 as f64);
+        let (x, y) = point;
+        if x < 0.0 || y < 0.0 || x >= width || y >= height {
+            return None;
+        }
+        let west = x < border;
+        let east = x >= width - border;
+        let north = y < border;
+        let south = y >= height - border;
+        Some(match (north, south, west, east) {
+            (true, _, true, _) => NorthWest,
+            (true, _, _, true) => NorthEast,
+            (_, true, true, _) => SouthWest,
+            (_, true, _, true) => SouthEast,
+            (true, _, _, _) => North,
+            (_, true, _, _) => South,
+            (_, _, true, _) => West,
+            (_, _, _, true) => East,
+            _ => return None
+        })
+    }
+
+    /// Position and size after dragging this edge by `delta` from a window
+    /// at `position` with `size`. Opposite edges stay in place and the size
+    /// does not shrink below one pixel.
+    pub fn resize(self, position: (i32, i32), size: (u32, u32), delta: (i32, i32))
+        -> ((i32, i32), (u32, u32))
+    {
+        use self::ResizeEdge::*;
+        let (x, width) = match self {
+            West | NorthWest | SouthWest =>
+                shrink_from_start(position.0, size.0, delta.0),
+            East | NorthEast | SouthEast => (position.0, grow(size.0, delta.0)),
+            _ => (position.0, size.0)
+        };
+        let (y, height) = match self {
+            North | NorthWest | NorthEast =>
+                shrink_from_start(position.1, size.1, delta.1),
+            South | SouthWest | SouthEast => (position.1, grow(size.1, delta.1)),
+            _ => (position.1, size.1)
+        };
+        ((x, y), (width, height))
+    }
+}
+
+fn grow(length: u32, delta: i32) -> u32 {
+    (length as i64 + delta as i64).max(1) as u32
+}
+
+fn shrink_from_start(start: i32, length: u32, delta: i32) -> (i32, u32) {
+    let new_length = grow(length, -delta);
+    (start + (length as i64 - new_length as i64) as i32, new_length)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::ResizeEdge;
+    use super::ResizeEdge::*;
+
+    #[test]
+    fn classifies_edges_and_corners() {
+        let at = |x, y| ResizeEdge::at((x, y), (100, 50), 4.0);
+        assert_eq!(at(1.0, 1.0), Some(NorthWest));
+        assert_eq!(at(99.0, 49.0), Some(SouthEast));
+        assert_eq!(at(50.0, 48.0), Some(South));
+        assert_eq!(at(2.0, 25.0), Some(West));
+        assert_eq!(at(50.0, 25.0), None);
+        assert_eq!(at(-1.0, 25.0), None);
+    }
+
+    #[test]
+    fn keeps_the_opposite_edge_in_place() {
+        assert_eq!(West.resize((10, 10), (100, 50), (20, 5)), ((30, 10), (80, 50)));
+        assert_eq!(SouthEast.resize((10, 10), (100, 50), (5, -60)),
+                   ((10, 10), (105, 1)));
+        assert_eq!(North.resize((10, 10), (100, 50), (0, 80)), ((10, 59), (100, 1)));
+    }
+}
@@ -0,0 +1,286 @@
+use std::sync::{Arc, RwLock};
+use carboxyl::{Signal, Stream};
+use glutin::MouseButton;
+use ::{Rect, ScrollDelta};
+
+/// What happened in a pointer event.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PointerAction {
+    Move,
+    Press(MouseButton),
+    Release(MouseButton),
+    Click(MouseButton, u32),
+    Wheel(ScrollDelta)
+}
+
+/// A pointer event together with the cursor position it happened at.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PointerEvent {
+    pub position: (f64, f64),
+    pub action: PointerAction
+}
+
+impl PointerEvent {
+    pub fn new(position: (f64, f64), action: PointerAction) -> PointerEvent {
+        PointerEvent { position: position, action: action }
+    }
+}
+
+/// The two passes an event makes through the regions containing its target.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Phase {
+    /// From the outermost parent down to the target.
+    Capture,
+    /// From the target up to the outermost parent.
+    Bubble
+}
+
+/// Decides whether a region consumes an event, which stops it from
+/// propagating any further.
+///
+/// Handlers run while the event is being dispatched, so they should only
+/// inspect it. React to the region's event streams instead.
+pub type Handler = Box<dyn Fn(&PointerEvent) -> bool + Send + Sync>;
+
+struct Route {
+    name: String,
+    parent: Option<String>,
+    z: i32,
+    rect: Signal<Rect>
+}
+
+#[derive(Default)]
+struct Routes {
+    routes: Vec<Route>,
+    handlers: Vec<(String, Phase, Handler)>
+}
+
+impl Routes {
+    /// The target and its ancestors, innermost first.
+    fn ancestry(&self, target: &str) -> Vec<String> {
+        let mut path = vec![target.to_string()];
+        while path.len() <= self.routes.len() {
+            let parent = self.routes.iter()
+                .find(|route| route.name == path[path.len() - 1])
+                .and_then(|route| route.parent.clone());
+            match parent {
+                Some(parent) => path.push(parent),
+                None => break
+            }
+        }
+        path
+    }
+
+    fn consumes(&self, name: &str, phase: Phase, event: &PointerEvent) -> bool {
+        self.handlers.iter()
+            .filter(|&&(ref handler_name, handler_phase, _)|
+                handler_name == name && handler_phase == phase)
+            .any(|(_, _, handler)| handler(event))
+    }
+
+    /// Every region the event reaches in each phase, in order, up to and
+    /// including the one that consumes it.
+    fn propagate(&self, target: &str, event: &PointerEvent) -> Vec<(String, Phase)> {
+        let ancestry = self.ancestry(target);
+        let capture = ancestry.iter().rev().map(|name| (name, Phase::Capture));
+        let bubble = ancestry.iter().map(|name| (name, Phase::Bubble));
+        let mut reached = vec![];
+        for (name, phase) in capture.chain(bubble) {
+            reached.push((name.clone(), phase));
+            if self.consumes(name, phase, event) {
+                break;
+            }
+        }
+        reached
+    }
+}
+
+/// The name of the topmost of `regions` (name, z-order, rectangle) that
+/// contains `point`.
+///
+/// Higher z-orders are on top. Among equal ones the region added last wins,
+/// as it would be drawn last.
+pub fn topmost<'a>(regions: &'a [(&'a str, i32, Rect)], point: (f64, f64))
+    -> Option<&'a str>
+{
+    regions.iter()
+        .enumerate()
+        .filter(|&(_, &(_, _, rect))| rect.contains(point))
+        .max_by_key(|&(index, &(_, z, _))| (z, index))
+        .map(|(_, &(name, _, _))| name)
+}
+
+/// A pointer event on its way through the regions.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Dispatch {
+    pub event: PointerEvent,
+    /// The topmost region under the pointer.
+    pub target: String,
+    /// The regions the event reached and in which phase, in order.
+    pub reached: Vec<(String, Phase)>
+}
+
+/// Dispatches pointer events to the topmost of a set of named regions.
+///
+/// Regions can be added and removed at any time, and hit-tested against
+/// their rectangles as they are when an event occurs. Regions may be nested
+/// in a parent, in which case an event travels from the outermost parent
+/// down to the target (capture) and back up (bubble), unless a handler
+/// consumes it on the way.
+#[derive(Clone)]
+pub struct Router {
+    routes: Arc<RwLock<Routes>>,
+    dispatched: Stream<Dispatch>
+}
+
+impl Router {
+    pub fn new(pointer: &Stream<PointerEvent>) -> Router {
+        let routes = Arc::new(RwLock::new(Routes::default()));
+        let hit_routes = routes.clone();
+        let dispatched = pointer
+            .filter_map(move |event| {
+                let routes: &Routes = &hit_routes.read().unwrap();
+                let regions: Vec<_> = routes.routes.iter()
+                    .map(|route| (&route.name[..], route.z, route.rect.sample()))
+                    .collect();
+                topmost(&regions, event.position)
+                    .map(|target| Dispatch {
+                        event: event,
+                        target: target.to_string(),
+                        reached: routes.propagate(target, &event)
+                    })
+            });
+        Router { routes: routes, dispatched: dispatched }
+    }
+
+    /// Add a region, replacing any previous one of the same name.
+    pub fn add(&self, name: &str, z: i32, rect: Signal<Rect>) {
+        self.insert(name, None, z, rect);
+    }
+
+    /// Add a region nested in `parent`, through which its events propagate.
+    ///
+    /// Nesting only affects propagation; hit-testing still picks the topmost
+    /// region by z-order.
+    pub fn add_child(&self, name: &str, parent: &str, z: i32, rect: Signal<Rect>) {
+        self.insert(name, Some(parent.to_string()), z, rect);
+    }
+
+    fn insert(&self, name: &str, parent: Option<String>, z: i32, rect: Signal<Rect>) {
+        self.remove(name);
+        self.routes.write().unwrap().routes.push(Route {
+            name: name.to_string(),
+            parent: parent,
+            z: z,
+            rect: rect
+        });
+    }
+
+    /// Remove a region and its handlers.
+    pub fn remove(&self, name: &str) {
+        let mut routes = self.routes.write().unwrap();
+        routes.routes.retain(|route| route.name != name);
+        routes.handlers.retain(|(handler_name, _, _)| handler_name != name);
+    }
+
+    /// Let `handler` consume events reaching the region `name` in `phase`.
+    pub fn on<F>(&self, name: &str, phase: Phase, handler: F)
+        where F: Fn(&PointerEvent) -> bool + Send + Sync + 'static
+    {
+        self.routes.write().unwrap()
+            .handlers.push((name.to_string(), phase, Box::new(handler)));
+    }
+
+    /// The pointer events reaching the region `name` while bubbling, which
+    /// includes every event it is the target of.
+    pub fn events(&self, name: &str) -> Stream<PointerEvent> {
+        self.phase_events(name, Phase::Bubble)
+    }
+
+    /// The pointer events reaching the region `name` while capturing, before
+    /// any region nested in it sees them.
+    pub fn captured(&self, name: &str) -> Stream<PointerEvent> {
+        self.phase_events(name, Phase::Capture)
+    }
+
+    fn phase_events(&self, name: &str, phase: Phase) -> Stream<PointerEvent> {
+        let name = name.to_string();
+        self.dispatched
+            .filter_map(move |dispatch| {
+                let reached = dispatch.reached.iter()
+                    .any(|&(ref reached, reached_phase)|
+                        *reached == name && reached_phase == phase);
+                if reached { Some(dispatch.event) } else { None }
+            })
+    }
+
+    /// All dispatched events with the name of the region they went to.
+    pub fn routed(&self) -> Stream<(String, PointerEvent)> {
+        self.dispatched.map(|dispatch| (dispatch.target, dispatch.event))
+    }
+
+    /// All dispatched events with their propagation.
+    pub fn dispatched(&self) -> Stream<Dispatch> {
+        self.dispatched.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use carboxyl::{Sink, Signal};
+    use glutin::MouseButton::Left;
+    use ::Rect;
+    use super::*;
+
+    #[test]
+    fn picks_the_topmost_region() {
+        let big = Rect::new(0.0, 0.0, 100.0, 100.0);
+        let small = Rect::new(10.0, 10.0, 10.0, 10.0);
+        let regions = [("panel", 0, big), ("button", 1, small), ("overlay", 0, big)];
+        assert_eq!(topmost(&regions, (15.0, 15.0)), Some("button"));
+        assert_eq!(topmost(&regions, (50.0, 50.0)), Some("overlay"));
+        assert_eq!(topmost(&regions, (150.0, 50.0)), None);
+    }
+
+    #[test]
+    fn routes_events_to_region_streams() {
+        let pointer = Sink::new();
+        let router = Router::new(&pointer.stream());
+        router.add("left", 0, Signal::new(Rect::new(0.0, 0.0, 50.0, 50.0)));
+        router.add("right", 0, Signal::new(Rect::new(50.0, 0.0, 50.0, 50.0)));
+        let left = router.events("left").fold(0, |n, _| n + 1);
+        let right = router.events("right").fold(0, |n, _| n + 1);
+        pointer.send(PointerEvent::new((10.0, 10.0), PointerAction::Press(Left)));
+        pointer.send(PointerEvent::new((60.0, 10.0), PointerAction::Move));
+        pointer.send(PointerEvent::new((60.0, 90.0), PointerAction::Move));
+        router.remove("right");
+        pointer.send(PointerEvent::new((60.0, 10.0), PointerAction::Move));
+        assert_eq!((left.sample(), right.sample()), (1, 1));
+    }
+
+    #[test]
+    fn captures_down_and_bubbles_up_until_consumed() {
+        let pointer = Sink::new();
+        let router = Router::new(&pointer.stream());
+        router.add("panel", 0, Signal::new(Rect::new(0.0, 0.0, 100.0, 100.0)));
+        let button = Rect::new(10.0, 10.0, 10.0, 10.0);
+        router.add_child("button", "panel", 1, Signal::new(button));
+        let reached = router.dispatched()
+            .map(|dispatch| dispatch.reached)
+            .hold(vec![]);
+        let press = PointerEvent::new((15.0, 15.0), PointerAction::Press(Left));
+        pointer.send(press);
+        assert_eq!(reached.sample(), vec![
+            ("panel".to_string(), Phase::Capture),
+            ("button".to_string(), Phase::Capture),
+            ("button".to_string(), Phase::Bubble),
+            ("panel".to_string(), Phase::Bubble)
+        ]);
+        router.on("button", Phase::Bubble, |event|
+            matches!(event.action, PointerAction::Press(_)));
+        let panel = router.events("panel").fold(0, |n, _| n + 1);
+        pointer.send(press);
+        pointer.send(PointerEvent::new((15.0, 15.0), PointerAction::Move));
+        assert_eq!(panel.sample(), 1);
+    }
+}
@@ -0,0 +1,119 @@
+use std::thread;
+use std::time::Duration;
+use idle::IdlePolicy;
+
+/// Timing policy of the run loop: when to tick and how to wait in between.
+pub trait Scheduler {
+    /// Whether a tick is due at `now`, given the time of the last input
+    /// event. Both are in nanoseconds.
+    fn due(&mut self, now: u64, last_input: u64) -> bool;
+
+    /// Wait until the next tick may be due.
+    fn wait(&mut self, now: u64);
+
+    /// Time budget of the tick that is due, if there is one.
+    fn budget(&self) -> Option<u64> { None }
+}
+
+/// Ticks at a fixed frame rate, sleeping in between. Ticks that were missed
+/// because of a long frame are skipped rather than caught up on.
+pub struct FixedRate {
+    fps: f64,
+    idle_policy: Option<IdlePolicy>,
+    tick_length: u64,
+    next_tick: Option<u64>
+}
+
+impl FixedRate {
+    pub fn new(fps: f64, idle_policy: Option<IdlePolicy>) -> FixedRate {
+        assert!(fps > 0.0);
+        FixedRate {
+            fps: fps,
+            idle_policy: idle_policy,
+            tick_length: (1e9 / fps) as u64,
+            next_tick: None
+        }
+    }
+}
+
+impl Scheduler for FixedRate {
+    fn due(&mut self, now: u64, last_input: u64) -> bool {
+        let fps = match self.idle_policy {
+            Some(policy) => policy.target_fps(self.fps, now.saturating_sub(last_input)),
+            None => self.fps
+        };
+        self.tick_length = (1e9 / fps) as u64;
+        let next_tick = self.next_tick.unwrap_or(now);
+        if now >= next_tick {
+            let diff = now - next_tick;
+            let delta = diff - diff % self.tick_length;
+            self.next_tick = Some(next_tick + delta + self.tick_length);
+            true
+        } else {
+            false
+        }
+    }
+
+    fn wait(&mut self, now: u64) {
+        if let Some(next_tick) = self.next_tick {
+            if next_tick > now {
+                let remaining = next_tick - now;
+                thread::sleep(Duration::new(
+                    remaining / 1_000_000_000,
+                    (remaining % 1_000_000_000) as u32));
+            }
+        }
+    }
+
+    fn budget(&self) -> Option<u64> {
+        Some(self.tick_length)
+    }
+}
+
+/// Ticks continuously, relying on the render closure blocking in
+/// `swap_buffers` until the display refreshes. The window must have been
+/// built with vsync enabled (`WindowBuilder::with_vsync`).
+pub struct VSync;
+
+impl Scheduler for VSync {
+    fn due(&mut self, _now: u64, _last_input: u64) -> bool { true }
+
+    fn wait(&mut self, _now: u64) {}
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use idle::IdlePolicy;
+    use super::{Scheduler, FixedRate};
+
+    const MS: u64 = 1_000_000;
+
+    #[test]
+    fn ticks_once_per_tick_length() {
+        let mut scheduler = FixedRate::new(100.0, None);
+        assert!(scheduler.due(0, 0));
+        assert!(!scheduler.due(5 * MS, 0));
+        assert!(scheduler.due(10 * MS, 0));
+    }
+
+    #[test]
+    fn skips_missed_ticks() {
+        let mut scheduler = FixedRate::new(100.0, None);
+        scheduler.due(0, 0);
+        assert!(scheduler.due(35 * MS, 0));
+        assert!(!scheduler.due(39 * MS, 0));
+        assert!(scheduler.due(40 * MS, 0));
+    }
+
+    #[test]
+    fn stretches_the_budget_while_idle() {
+        let policy = IdlePolicy::new(Duration::from_secs(1), 10.0);
+        let mut scheduler = FixedRate::new(100.0, Some(policy));
+        scheduler.due(0, 0);
+        assert_eq!(scheduler.budget(), Some(10 * MS));
+        scheduler.due(2000 * MS, 0);
+        assert_eq!(scheduler.budget(), Some(100 * MS));
+    }
+}
@@ -0,0 +1,66 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use glutin;
+
+/// Where a driver gets its events from, and the window it controls.
+///
+/// Only polling is required, so that sources without an actual window, such
+/// as test fixtures, are easy to write. The window controls default to doing
+/// nothing.
+pub trait EventSource {
+    /// Take all events that are currently pending.
+    fn poll_events(&mut self) -> Vec<glutin::Event>;
+
+    fn inner_size(&self) -> Option<(u32, u32)> { None }
+
+    fn position(&self) -> Option<(i32, i32)> { None }
+
+    fn set_inner_size(&self, _width: u32, _height: u32) {}
+
+    fn set_position(&self, _x: i32, _y: i32) {}
+}
+
+impl EventSource for glutin::Window {
+    fn poll_events(&mut self) -> Vec<glutin::Event> {
+        glutin::Window::poll_events(self).collect()
+    }
+
+    fn inner_size(&self) -> Option<(u32, u32)> {
+        self.get_inner_size()
+    }
+
+    fn position(&self) -> Option<(i32, i32)> {
+        self.get_position()
+    }
+
+    fn set_inner_size(&self, width: u32, height: u32) {
+        glutin::Window::set_inner_size(self, width, height)
+    }
+
+    fn set_position(&self, x: i32, y: i32) {
+        glutin::Window::set_position(self, x, y)
+    }
+}
+
+/// Lets the caller keep a handle on a source that a driver owns.
+impl<W: EventSource> EventSource for Rc<RefCell<W>> {
+    fn poll_events(&mut self) -> Vec<glutin::Event> {
+        self.borrow_mut().poll_events()
+    }
+
+    fn inner_size(&self) -> Option<(u32, u32)> {
+        self.borrow().inner_size()
+    }
+
+    fn position(&self) -> Option<(i32, i32)> {
+        self.borrow().position()
+    }
+
+    fn set_inner_size(&self, width: u32, height: u32) {
+        self.borrow().set_inner_size(width, height)
+    }
+
+    fn set_position(&self, x: i32, y: i32) {
+        self.borrow().set_position(x, y)
+    }
+}
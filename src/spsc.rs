@@ -0,0 +1,136 @@
+use std::cell::UnsafeCell;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use glutin;
+use source::EventSource;
+
+/// Ring buffer with one slot kept free to tell a full buffer from an empty
+/// one.
+///
+/// Only the producer writes `tail` and the slot at it, only the consumer
+/// writes `head` and the slot at it, so neither side ever waits for the
+/// other.
+struct Ring {
+    slots: Box<[UnsafeCell<Option<glutin::Event>>]>,
+    head: AtomicUsize,
+    tail: AtomicUsize
+}
+
+// Slots are handed over between the two ends through the release/acquire
+// pairs on `head` and `tail`, so no slot is accessed from both at once.
+unsafe impl Sync for Ring {}
+
+impl Ring {
+    fn next(&self, index: usize) -> usize {
+        (index + 1) % self.slots.len()
+    }
+}
+
+/// Feeds events into a lock-free ring buffer, e.g. from a realtime thread.
+///
+/// There is exactly one producer per buffer, so this is not `Clone`.
+pub struct SpscProducer {
+    ring: Arc<Ring>
+}
+
+impl SpscProducer {
+    /// Push an event without blocking, handing it back if the buffer is
+    /// full.
+    pub fn push(&mut self, event: glutin::Event) -> Result<(), glutin::Event> {
+        let tail = self.ring.tail.load(Ordering::Relaxed);
+        let next = self.ring.next(tail);
+        if next == self.ring.head.load(Ordering::Acquire) {
+            return Err(event);
+        }
+        unsafe { *self.ring.slots[tail].get() = Some(event) };
+        self.ring.tail.store(next, Ordering::Release);
+        Ok(())
+    }
+}
+
+/// The driver's end of a lock-free ring buffer.
+pub struct SpscSource {
+    ring: Arc<Ring>
+}
+
+impl SpscSource {
+    fn pop(&mut self) -> Option<glutin::Event> {
+        let head = self.ring.head.load(Ordering::Relaxed);
+        if head == self.ring.tail.load(Ordering::Acquire) {
+            return None;
+        }
+        let event = unsafe { (*self.ring.slots[head].get()).take() };
+        self.ring.head.store(self.ring.next(head), Ordering::Release);
+        event
+    }
+}
+
+impl EventSource for SpscSource {
+    fn poll_events(&mut self) -> Vec<glutin::Event> {
+        let mut events = Vec::new();
+        while let Some(event) = self.pop() {
+            events.push(event);
+        }
+        events
+    }
+}
+
+/// A single-producer/single-consumer buffer of at most `capacity` events
+/// that neither side locks.
+///
+/// Unlike `bounded_queue` the producer never contends for a mutex with the
+/// driver, at the price of having to handle a full buffer itself.
+pub fn spsc_queue(capacity: usize) -> (SpscProducer, SpscSource) {
+    assert!(capacity > 0);
+    let ring = Arc::new(Ring {
+        slots: (0..capacity + 1).map(|_| UnsafeCell::new(None)).collect(),
+        head: AtomicUsize::new(0),
+        tail: AtomicUsize::new(0)
+    });
+    (SpscProducer { ring: ring.clone() }, SpscSource { ring: ring })
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+    use glutin::Event::{MouseMoved, ReceivedCharacter};
+    use source::EventSource;
+    use super::spsc_queue;
+
+    #[test]
+    fn hands_back_events_when_full() {
+        let (mut producer, mut source) = spsc_queue(2);
+        assert!(producer.push(ReceivedCharacter('a')).is_ok());
+        assert!(producer.push(ReceivedCharacter('b')).is_ok());
+        assert!(producer.push(ReceivedCharacter('c')).is_err());
+        assert_eq!(source.poll_events().len(), 2);
+        assert!(producer.push(ReceivedCharacter('c')).is_ok());
+        assert_eq!(format!("{:?}", source.poll_events()),
+                   format!("{:?}", vec![ReceivedCharacter('c')]));
+    }
+
+    #[test]
+    fn delivers_in_order_across_threads() {
+        let (mut producer, mut source) = spsc_queue(4);
+        let thread = thread::spawn(move || {
+            for x in 0..1000 {
+                let mut event = MouseMoved(x, 0);
+                while let Err(full) = producer.push(event) {
+                    event = full;
+                    thread::yield_now();
+                }
+            }
+        });
+        let mut received = Vec::new();
+        while received.len() < 1000 {
+            for event in source.poll_events() {
+                if let MouseMoved(x, _) = event {
+                    received.push(x);
+                }
+            }
+        }
+        thread.join().unwrap();
+        assert_eq!(received, (0..1000).collect::<Vec<_>>());
+    }
+}
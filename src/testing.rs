@@ -0,0 +1,287 @@
+//! Utilities for testing signal networks built on a driver.
+
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use carboxyl::{Signal, Stream};
+use glutin;
+use driver::WindowDriver;
+use source::EventSource;
+use ::{Context, Event, Timed};
+
+/// A manually advanced clock, in nanoseconds.
+#[derive(Clone, Debug, Default)]
+pub struct MockClock {
+    now: Arc<AtomicU64>
+}
+
+impl MockClock {
+    pub fn new(now: u64) -> MockClock {
+        MockClock { now: Arc::new(AtomicU64::new(now)) }
+    }
+
+    pub fn now(&self) -> u64 {
+        self.now.load(Ordering::SeqCst)
+    }
+
+    pub fn set(&self, now: u64) {
+        self.now.store(now, Ordering::SeqCst);
+    }
+
+    pub fn advance(&self, duration: u64) {
+        self.now.fetch_add(duration, Ordering::SeqCst);
+    }
+}
+
+/// An event source that hands out one prepared batch of events per poll.
+#[derive(Default)]
+pub struct ScriptSource {
+    batches: VecDeque<Vec<glutin::Event>>,
+    size: Option<(u32, u32)>
+}
+
+impl ScriptSource {
+    pub fn new() -> ScriptSource {
+        ScriptSource::default()
+    }
+
+    /// Queue a batch of events to be returned by a later poll.
+    pub fn push(&mut self, batch: Vec<glutin::Event>) {
+        self.batches.push_back(batch);
+    }
+
+    /// Report a window size, as a real window would have at creation.
+    pub fn with_size(mut self, width: u32, height: u32) -> ScriptSource {
+        self.size = Some((width, height));
+        self
+    }
+}
+
+impl EventSource for ScriptSource {
+    fn poll_events(&mut self) -> Vec<glutin::Event> {
+        self.batches.pop_front().unwrap_or_default()
+    }
+
+    fn inner_size(&self) -> Option<(u32, u32)> {
+        self.size
+    }
+}
+
+/// A fake window that tests push events into while a driver owns it.
+///
+/// Share it with the driver through `MockWindow::shared`; the driver accepts
+/// the `Rc<RefCell<_>>` as its event source.
+#[derive(Debug, Default)]
+pub struct MockWindow {
+    pending: Vec<glutin::Event>,
+    should_close: bool,
+    size: Cell<(u32, u32)>,
+    position: Cell<(i32, i32)>
+}
+
+impl MockWindow {
+    pub fn new() -> MockWindow {
+        MockWindow::default()
+    }
+
+    pub fn shared() -> Rc<RefCell<MockWindow>> {
+        Rc::new(RefCell::new(MockWindow::new()))
+    }
+
+    /// Queue an event for the next poll.
+    pub fn push(&mut self, event: glutin::Event) {
+        self.pending.push(event);
+    }
+
+    /// Make the next poll report a close request.
+    pub fn set_should_close(&mut self, value: bool) {
+        self.should_close = value;
+    }
+
+    pub fn should_close(&self) -> bool {
+        self.should_close
+    }
+}
+
+impl EventSource for MockWindow {
+    fn poll_events(&mut self) -> Vec<glutin::Event> {
+        let mut events: Vec<_> = self.pending.drain(..).collect();
+        if self.should_close {
+            events.push(glutin::Event::Closed);
+        }
+        events
+    }
+
+    fn inner_size(&self) -> Option<(u32, u32)> {
+        Some(self.size.get())
+    }
+
+    fn position(&self) -> Option<(i32, i32)> {
+        Some(self.position.get())
+    }
+
+    fn set_inner_size(&self, width: u32, height: u32) {
+        self.size.set((width, height));
+    }
+
+    fn set_position(&self, x: i32, y: i32) {
+        self.position.set((x, y));
+    }
+}
+
+/// Feed a script of timed event batches through a driver and sample a signal
+/// after each step.
+///
+/// `probe` derives the signal under test from the driver before the script
+/// runs. Each entry of `script` is one tick at the given time (in
+/// nanoseconds) with the events dispatched during it, which are timestamped
+/// with a mock clock set to that time. The sampled values can then be
+/// compared against a known-good snapshot.
+pub fn snapshot<T, F>(script: Vec<(u64, Vec<glutin::Event>)>, probe: F) -> Vec<T>
+    where T: Clone + 'static,
+          F: FnOnce(&mut WindowDriver<ScriptSource>) -> Signal<T>
+{
+    let mut source = ScriptSource::new();
+    let times: Vec<u64> = script.iter().map(|&(time, _)| time).collect();
+    for (_, batch) in script {
+        source.push(batch);
+    }
+    let clock = MockClock::new(0);
+    let mut driver = WindowDriver::new(source);
+    let driver_clock = clock.clone();
+    driver.set_clock(move || driver_clock.now());
+    let signal = probe(&mut driver);
+    times.into_iter()
+        .map(|time| {
+            clock.set(time);
+            driver.step(time);
+            signal.sample()
+        })
+        .collect()
+}
+
+/// Accumulate every value a stream fires, in order.
+///
+/// Pairs with `snapshot` to check a whole event sequence at once.
+pub fn collect<T: Clone + Send + Sync + 'static>(stream: &Stream<T>) -> Signal<Vec<T>> {
+    stream.fold(Vec::new(), |mut all, value| {
+        all.push(value);
+        all
+    })
+}
+
+/// Final state of a headless simulation run.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Simulation {
+    /// The context after the last tick.
+    pub context: Context,
+    /// Every event dispatched during the run, including the final `Shutdown`.
+    pub events: Vec<Timed<Event>>,
+    /// Number of ticks that were run.
+    pub ticks: usize,
+    /// Whether the run ended because the window was closed.
+    pub closed: bool
+}
+
+/// Run a script of timed event batches through the whole dispatch pipeline
+/// without an OS window and return the final state.
+///
+/// Time is virtual (see `snapshot`), so runs are reproducible on machines
+/// without a display. The script ends early if it closes the window.
+pub fn simulate(script: Vec<(u64, Vec<glutin::Event>)>) -> Simulation {
+    let mut source = ScriptSource::new();
+    let times: Vec<u64> = script.iter().map(|&(time, _)| time).collect();
+    for (_, batch) in script {
+        source.push(batch);
+    }
+    let clock = MockClock::new(0);
+    let mut driver = WindowDriver::new(source);
+    let driver_clock = clock.clone();
+    driver.set_clock(move || driver_clock.now());
+    let context = driver.context();
+    let events = collect(&driver.timed_events());
+    let mut ticks = 0;
+    let mut closed = false;
+    for time in times {
+        clock.set(time);
+        ticks += 1;
+        if !driver.step(time) {
+            closed = true;
+            break;
+        }
+    }
+    driver.shutdown();
+    Simulation {
+        context: context.sample(),
+        events: events.sample(),
+        ticks: ticks,
+        closed: closed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glutin::Event::{Closed, MouseMoved, MouseWheel, ReceivedCharacter};
+    use glutin::MouseScrollDelta::PixelDelta;
+    use glutin::TouchPhase::Moved;
+    use ::Event;
+    use driver::WindowDriver;
+    use super::{snapshot, simulate, MockWindow};
+
+    #[test]
+    fn samples_signals_after_every_step() {
+        let values = snapshot(
+            vec![
+                (0, vec![]),
+                (10, vec![MouseWheel(PixelDelta(0.0, 3.0), Moved)]),
+                (20, vec![
+                    MouseWheel(PixelDelta(1.0, 3.0), Moved),
+                    MouseMoved(4, 2)
+                ])
+            ],
+            |driver| driver.context().map(|context| context.cursor.wheel)
+        );
+        assert_eq!(values, vec![(0.0, 0.0), (0.0, 3.0), (1.0, 6.0)]);
+    }
+
+    #[test]
+    fn timestamps_events_with_the_script_time() {
+        let times = snapshot(
+            vec![(5, vec![]), (70, vec![MouseWheel(PixelDelta(0.0, 1.0), Moved)])],
+            |driver| driver.timed_wheel()
+                .map(|delta| delta.time)
+                .hold(0)
+        );
+        assert_eq!(times, vec![0, 70]);
+    }
+
+    #[test]
+    fn simulates_until_the_window_closes() {
+        let result = simulate(vec![
+            (0, vec![ReceivedCharacter('a'), MouseMoved(3, 4)]),
+            (10, vec![Closed]),
+            (20, vec![ReceivedCharacter('b')])
+        ]);
+        assert!(result.closed);
+        assert_eq!(result.ticks, 2);
+        assert_eq!(result.context.cursor.position, (3.0, 4.0));
+        let events: Vec<Event> = result.events.into_iter()
+            .map(|event| event.value)
+            .collect();
+        assert_eq!(events, vec![Event::Text("a".to_string()), Event::Shutdown]);
+    }
+
+    #[test]
+    fn mock_window_is_driven_through_a_shared_handle() {
+        let window = MockWindow::shared();
+        let mut driver = WindowDriver::new(window.clone());
+        let context = driver.context();
+        window.borrow_mut().push(MouseMoved(7, 8));
+        assert!(driver.step(0));
+        assert_eq!(context.sample().cursor.position, (7.0, 8.0));
+        window.borrow_mut().set_should_close(true);
+        assert!(!driver.step(1));
+    }
+}
@@ -0,0 +1,340 @@
+use carboxyl::{Signal, Stream};
+use glutin::VirtualKeyCode;
+use ::{Event, Button, Modifiers};
+
+/// State of a single-line text field.
+///
+/// Positions are in characters. The selection spans from `anchor` to
+/// `cursor`, in either direction.
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub struct TextField {
+    pub text: String,
+    pub cursor: usize,
+    pub anchor: Option<usize>
+}
+
+impl TextField {
+    pub fn new(text: &str) -> TextField {
+        TextField { text: text.to_string(), cursor: text.chars().count(), anchor: None }
+    }
+
+    fn len(&self) -> usize {
+        self.text.chars().count()
+    }
+
+    fn byte(&self, index: usize) -> usize {
+        self.text.char_indices().nth(index).map_or(self.text.len(), |(byte, _)| byte)
+    }
+
+    /// The selected range, start first, if it is not empty.
+    pub fn selection(&self) -> Option<(usize, usize)> {
+        match self.anchor {
+            Some(anchor) if anchor != self.cursor =>
+                Some((anchor.min(self.cursor), anchor.max(self.cursor))),
+            _ => None
+        }
+    }
+
+    pub fn selected_text(&self) -> String {
+        self.selection()
+            .map_or(String::new(), |(start, end)|
+                self.text[self.byte(start)..self.byte(end)].to_string())
+    }
+
+    /// Remove the selection, if any, and return whether there was one. An
+    /// empty selection is dropped, so that it does not grow into one.
+    fn delete_selection(&mut self) -> bool {
+        match self.selection() {
+            Some((start, end)) => {
+                let range = self.byte(start)..self.byte(end);
+                self.text.replace_range(range, "");
+                self.cursor = start;
+                self.anchor = None;
+                true
+            },
+            None => {
+                self.anchor = None;
+                false
+            }
+        }
+    }
+
+    /// Move the cursor, extending the selection or dropping it.
+    fn move_to(&mut self, index: usize, select: bool) {
+        if select {
+            self.anchor = self.anchor.or(Some(self.cursor));
+        } else {
+            self.anchor = None;
+        }
+        self.cursor = index;
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TextEdit {
+    /// Replace the selection with text, as typing does.
+    Insert(String),
+    Backspace,
+    Delete,
+    /// Cursor movement, extending the selection if the flag is set.
+    Left(bool),
+    Right(bool),
+    Home(bool),
+    End(bool),
+    SelectAll,
+    /// Copy and remove the selection.
+    Cut,
+    Copy,
+    /// Insert text from the clipboard.
+    Paste(String),
+    /// A mouse press at a character position, which places the cursor.
+    Press(usize),
+    /// Dragging the mouse to a character position, which selects up to it.
+    DragTo(usize)
+}
+
+impl TextEdit {
+    pub fn apply(self, current: TextField) -> TextField {
+        use self::TextEdit::*;
+        let mut field = current;
+        let len = field.len();
+        let selection = field.selection();
+        match self {
+            Insert(text) | Paste(text) => {
+                field.delete_selection();
+                let byte = field.byte(field.cursor);
+                field.text.insert_str(byte, &text);
+                field.cursor += text.chars().count();
+            },
+            Backspace =>
+                if !field.delete_selection() && field.cursor > 0 {
+                    let start = field.byte(field.cursor - 1);
+                    let end = field.byte(field.cursor);
+                    field.text.replace_range(start..end, "");
+                    field.cursor -= 1;
+                },
+            Delete =>
+                if !field.delete_selection() && field.cursor < len {
+                    let start = field.byte(field.cursor);
+                    let end = field.byte(field.cursor + 1);
+                    field.text.replace_range(start..end, "");
+                },
+            // Without shift, arrows collapse a selection towards their side
+            Left(select) => match selection {
+                Some((start, _)) if !select => field.move_to(start, false),
+                Some(_) | None => {
+                    let index = field.cursor.saturating_sub(1);
+                    field.move_to(index, select);
+                }
+            },
+            Right(select) => match selection {
+                Some((_, end)) if !select => field.move_to(end, false),
+                Some(_) | None => {
+                    let index = (field.cursor + 1).min(len);
+                    field.move_to(index, select);
+                }
+            },
+            Home(select) => field.move_to(0, select),
+            End(select) => field.move_to(len, select),
+            SelectAll => {
+                field.anchor = Some(0);
+                field.cursor = len;
+            },
+            Cut => { field.delete_selection(); },
+            Copy => (),
+            Press(index) => field.move_to(index.min(len), false),
+            DragTo(index) => field.move_to(index.min(len), true)
+        }
+        field
+    }
+}
+
+/// The edit a keyboard event stands for, given the held modifiers.
+///
+/// Text typed with Ctrl held belongs to a shortcut, unless Alt is held as
+/// well, as Windows reports AltGr, which types characters like `@` or `€`.
+fn key_edit(modifiers: &Modifiers, event: Event) -> Option<TextEdit> {
+    use glutin::VirtualKeyCode::*;
+    let shift = modifiers.shift();
+    let shortcut = modifiers.ctrl() && !modifiers.alt();
+    match event {
+        Event::Text(ref text)
+            if shortcut || text.chars().any(char::is_control) => None,
+        Event::Text(text) => Some(TextEdit::Insert(text)),
+        Event::Press(Button::Keyboard(key)) |
+        Event::Repeat(Button::Keyboard(key)) => match key {
+            Back => Some(TextEdit::Backspace),
+            Delete => Some(TextEdit::Delete),
+            Left => Some(TextEdit::Left(shift)),
+            Right => Some(TextEdit::Right(shift)),
+            Home => Some(TextEdit::Home(shift)),
+            End => Some(TextEdit::End(shift)),
+            A if modifiers.ctrl() => Some(TextEdit::SelectAll),
+            X if modifiers.ctrl() => Some(TextEdit::Cut),
+            C if modifiers.ctrl() => Some(TextEdit::Copy),
+            _ => None
+        },
+        _ => None
+    }
+}
+
+/// A text field driven by keyboard events.
+#[derive(Clone)]
+pub struct TextFieldModel {
+    pub field: Signal<TextField>,
+    /// Text that was cut or copied, to be put on the clipboard.
+    pub copied: Stream<String>,
+    /// Fires on Ctrl+V. Answer with a `TextEdit::Paste` of the clipboard
+    /// contents.
+    pub paste_requests: Stream<()>
+}
+
+/// Build a text field from keyboard events and further edits.
+///
+/// `events` should only contain the events meant for the field, such as
+/// `FocusManager::keyboard` provides. `modifiers` should be tracked from all
+/// of the window's events instead, such as `WindowDriver::modifiers`, since
+/// a modifier may be released after focus has moved on. `edits` carries
+/// what the field cannot determine itself: mouse presses and drags mapped
+/// to character positions, which depends on the font, and pasted text,
+/// since the window backend has no clipboard access.
+pub fn text_field(initial: TextField, events: &Stream<Event>,
+                  modifiers: &Signal<Modifiers>, edits: &Stream<TextEdit>)
+    -> TextFieldModel
+{
+    let keys = modifiers
+        .snapshot(events, |modifiers, event| key_edit(&modifiers, event))
+        .filter_some();
+    let paste_requests = modifiers
+        .snapshot(events, |modifiers, event| match event {
+            Event::Press(Button::Keyboard(VirtualKeyCode::V)) if modifiers.ctrl() =>
+                Some(()),
+            _ => None
+        })
+        .filter_some();
+    let all_edits = keys.merge(edits);
+    let field = all_edits.fold(initial, |field, edit| edit.apply(field));
+    // Snapshots see the field from before the edit is folded in
+    let copied = field
+        .snapshot(&all_edits, |field, edit| match edit {
+            TextEdit::Cut | TextEdit::Copy => Some(field.selected_text()),
+            _ => None
+        })
+        .filter_map(|text| text.filter(|text| !text.is_empty()));
+    TextFieldModel { field: field, copied: copied, paste_requests: paste_requests }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use carboxyl::Sink;
+    use carboxyl::{Signal, Stream};
+    use glutin::VirtualKeyCode::{LShift, LControl, C, V};
+    use ::{Button, Event, FocusManager, Modifiers};
+    use super::*;
+    use super::TextEdit::*;
+
+    fn modifiers(events: &Stream<Event>) -> Signal<Modifiers> {
+        events.fold(Modifiers::default(), |modifiers, event| modifiers.update(&event))
+    }
+
+    fn edit(text: &str, edits: Vec<TextEdit>) -> TextField {
+        edits.into_iter().fold(TextField::new(text), |field, edit| edit.apply(field))
+    }
+
+    #[test]
+    fn inserts_and_deletes_at_the_cursor() {
+        let field = edit("héllo", vec![Left(false), Backspace, Insert("ł".into())]);
+        assert_eq!(field.text, "hélło");
+        assert_eq!(edit("ab", vec![Home(false), Delete]).text, "b");
+    }
+
+    #[test]
+    fn selects_with_shift_and_replaces_the_selection() {
+        let field = edit("hello", vec![Left(true), Left(true)]);
+        assert_eq!(field.selected_text(), "lo");
+        let field = Insert("p!".to_string()).apply(field);
+        assert_eq!((&field.text[..], field.cursor), ("help!", 5));
+        assert_eq!(field.selection(), None);
+    }
+
+    #[test]
+    fn arrows_collapse_the_selection() {
+        assert_eq!(edit("hello", vec![SelectAll, Left(false)]).cursor, 0);
+        assert_eq!(edit("hello", vec![Press(1), DragTo(3), Right(false)]).cursor, 3);
+    }
+
+    #[test]
+    fn deletes_single_characters_after_a_click() {
+        assert_eq!(edit("hello", vec![Press(3), Backspace, Backspace]).text, "hlo");
+        let field = edit("hello", vec![Left(true), Right(true), Backspace, Backspace]);
+        assert_eq!(field.text, "hel");
+    }
+
+    #[test]
+    fn selects_by_dragging() {
+        let field = edit("hello", vec![Press(4), DragTo(1)]);
+        assert_eq!(field.selected_text(), "ell");
+        assert_eq!(Cut.apply(field).text, "ho");
+    }
+
+    #[test]
+    fn maps_keys_and_reports_clipboard_actions() {
+        let events = Sink::new();
+        let edits = Sink::new();
+        let model = text_field(TextField::default(), &events.stream(),
+                               &modifiers(&events.stream()), &edits.stream());
+        let copied = model.copied.hold(String::new());
+        let pastes = model.paste_requests.fold(0, |n, ()| n + 1);
+        let key = |key| Event::Press(Button::Keyboard(key));
+        events.send(Event::Text("ab".to_string()));
+        events.send(key(LShift));
+        events.send(key(::glutin::VirtualKeyCode::Left));
+        events.send(Event::Release(Button::Keyboard(LShift)));
+        events.send(key(LControl));
+        events.send(key(C));
+        events.send(Event::Text("\u{3}".to_string()));
+        events.send(key(V));
+        edits.send(Paste("xy".to_string()));
+        assert_eq!(copied.sample(), "b");
+        assert_eq!(pastes.sample(), 1);
+        assert_eq!(model.field.sample().text, "axy");
+    }
+
+    #[test]
+    fn types_altgr_characters_but_not_ctrl_shortcuts() {
+        use glutin::VirtualKeyCode::RAlt;
+        let events = Sink::new();
+        let edits = Sink::new();
+        let model = text_field(TextField::default(), &events.stream(),
+                               &modifiers(&events.stream()), &edits.stream());
+        events.send(Event::Press(Button::Keyboard(LControl)));
+        events.send(Event::Text("v".to_string()));
+        events.send(Event::Press(Button::Keyboard(RAlt)));
+        events.send(Event::Text("@".to_string()));
+        assert_eq!(model.field.sample().text, "@");
+    }
+
+    #[test]
+    fn modifiers_released_in_another_widget_do_not_stick() {
+        use glutin::VirtualKeyCode::{Left, Tab};
+        let events = Sink::new();
+        let edits = Sink::new();
+        let held = modifiers(&events.stream());
+        let focus = FocusManager::new(&events.stream(), &held);
+        focus.register("a");
+        focus.register("b");
+        focus.focus(Some("a"));
+        let model = text_field(TextField::new("ab"), &focus.keyboard("a"),
+                               &held, &edits.stream());
+        let key = |key| Event::Press(Button::Keyboard(key));
+        let release = |key| Event::Release(Button::Keyboard(key));
+        for event in vec![key(LShift), key(Tab), release(Tab), release(LShift),
+                          key(Tab), release(Tab), key(Left)] {
+            events.send(event);
+        }
+        assert_eq!(focus.focused().sample(), Some("a".to_string()));
+        let field = model.field.sample();
+        assert_eq!((field.cursor, field.selection()), (1, None));
+    }
+}
@@ -0,0 +1,247 @@
+//! Stream operations driven by the run loop's clock.
+//!
+//! carboxyl has no notion of wall time, so these take the driver's tick
+//! stream (see `WindowDriver::ticks`) as their clock. Values that do not
+//! carry a time of their own are stamped with the latest tick.
+
+use std::collections::VecDeque;
+use std::time::Duration;
+use carboxyl::{Signal, Stream};
+use idle::nanoseconds;
+use debounce::{Debounce, DebounceUpdate};
+
+/// Time of the last value let through by a rate limit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct Throttle {
+    pub last: Option<u64>
+}
+
+impl Throttle {
+    /// Whether a value at `now` is at least `interval` after the last one.
+    pub fn allows(&self, now: u64, interval: u64) -> bool {
+        self.last.is_none_or(|last| now.saturating_sub(last) >= interval)
+    }
+
+    /// Record a value at `now`, if it is allowed through.
+    pub fn pass(self, now: u64, interval: u64) -> Throttle {
+        if self.allows(now, interval) {
+            Throttle { last: Some(now) }
+        } else {
+            self
+        }
+    }
+}
+
+/// Values waiting to be released at a later time, in order of arrival.
+#[derive(Clone, Debug, PartialEq, Default)]
+pub struct DelayQueue<T> {
+    pub pending: VecDeque<(u64, T)>
+}
+
+impl<T: Clone> DelayQueue<T> {
+    pub fn new() -> DelayQueue<T> {
+        DelayQueue { pending: VecDeque::new() }
+    }
+
+    /// Queue `value` for release at `due`.
+    pub fn push(mut self, due: u64, value: T) -> DelayQueue<T> {
+        self.pending.push_back((due, value));
+        self
+    }
+
+    /// The values that are due at `now`.
+    pub fn due(&self, now: u64) -> Vec<T> {
+        self.pending.iter()
+            .take_while(|&&(due, _)| due <= now)
+            .map(|(_, value)| value.clone())
+            .collect()
+    }
+
+    /// Drop the values that are due at `now`.
+    pub fn advance(mut self, now: u64) -> DelayQueue<T> {
+        while self.pending.front().is_some_and(|&(due, _)| due <= now) {
+            self.pending.pop_front();
+        }
+        self
+    }
+}
+
+/// A fixed period, independent of the frame rate.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Interval {
+    /// When the period is next due, in nanoseconds.
+    pub next: u64,
+    pub period: u64
+}
+
+impl Interval {
+    /// An interval first due one period after `start`.
+    pub fn new(start: u64, period: u64) -> Interval {
+        assert!(period > 0);
+        Interval { next: start + period, period: period }
+    }
+
+    /// The scheduled times of all periods that elapsed up to `now`.
+    ///
+    /// After a long frame this catches up with every missed period rather
+    /// than skipping them.
+    pub fn advance(&mut self, now: u64) -> Vec<u64> {
+        let mut due = vec![];
+        while self.next <= now {
+            due.push(self.next);
+            self.next += self.period;
+        }
+        due
+    }
+}
+
+/// Stamp the values of `stream` with the time of the latest tick.
+fn stamped<T>(stream: &Stream<T>, ticks: &Stream<u64>) -> Stream<(u64, T)>
+    where T: Clone + Send + Sync + 'static
+{
+    ticks.hold(0).snapshot(stream, |now, value| (now, value))
+}
+
+/// Let a value through at most once per `interval`, dropping the rest.
+///
+/// The first value always passes.
+pub fn throttle<T>(stream: &Stream<T>, ticks: &Stream<u64>, interval: Duration)
+    -> Stream<T>
+    where T: Clone + Send + Sync + 'static
+{
+    let interval = nanoseconds(interval);
+    let stamped = stamped(stream, ticks);
+    // Snapshots see the state from before the value is folded in
+    stamped.map(|(now, _)| now)
+        .fold(Throttle::default(), move |old, now| old.pass(now, interval))
+        .snapshot(&stamped, move |state, (now, value)|
+            if state.allows(now, interval) { Some(value) } else { None })
+        .filter_map(|value| value)
+}
+
+/// Emit the latest value once no new value arrived for `quiet`.
+///
+/// The check runs on every tick, so the value is emitted on the first tick
+/// after the quiet period.
+pub fn debounce<T>(stream: &Stream<T>, ticks: &Stream<u64>, quiet: Duration)
+    -> Stream<T>
+    where T: Clone + Send + Sync + 'static
+{
+    let quiet = nanoseconds(quiet);
+    let inputs = stamped(stream, ticks)
+        .map(|(now, value)| DebounceUpdate::Input(now, value));
+    inputs.merge(&ticks.map(DebounceUpdate::Advance))
+        .fold(Debounce::new(), move |old, update| update.apply(old, quiet))
+        .snapshot(ticks, move |state, now| state.due(now, quiet))
+        .filter_map(|value| value)
+}
+
+/// Sample `signal` on the first tick of every `interval`.
+pub fn sample_every<T>(signal: &Signal<T>, ticks: &Stream<u64>, interval: Duration)
+    -> Stream<T>
+    where T: Clone + Send + Sync + 'static
+{
+    let interval = nanoseconds(interval);
+    let due = ticks
+        .fold(Throttle::default(), move |old, now| old.pass(now, interval))
+        .snapshot(ticks, move |state, now| state.allows(now, interval));
+    signal.snapshot(&due, |value, due| if due { Some(value) } else { None })
+        .filter_map(|value| value)
+}
+
+/// Fire once, with the tick time, on the first tick at or after `deadline`
+/// (in nanoseconds).
+pub fn after(ticks: &Stream<u64>, deadline: u64) -> Stream<u64> {
+    ticks.fold(false, move |fired, now| fired || now >= deadline)
+        .snapshot(ticks, move |fired, now|
+            if !fired && now >= deadline { Some(now) } else { None })
+        .filter_map(|now| now)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use carboxyl::{Sink, Signal};
+    use testing::collect;
+    use super::*;
+
+    #[test]
+    fn throttle_allows_one_value_per_interval() {
+        let throttle = Throttle::default().pass(10, 100);
+        assert!(!throttle.allows(109, 100));
+        assert_eq!(throttle.pass(50, 100), throttle);
+        assert_eq!(throttle.pass(110, 100).last, Some(110));
+    }
+
+    #[test]
+    fn throttles_a_stream_by_tick_time() {
+        let ticks = Sink::new();
+        let values = Sink::new();
+        let out = collect(&throttle(&values.stream(), &ticks.stream(),
+                                    Duration::new(0, 100)));
+        values.send(1);
+        values.send(2);
+        ticks.send(50);
+        values.send(3);
+        ticks.send(100);
+        values.send(4);
+        values.send(5);
+        assert_eq!(out.sample(), vec![1, 4]);
+    }
+
+    #[test]
+    fn debounces_a_stream_by_tick_time() {
+        let ticks = Sink::new();
+        let values = Sink::new();
+        let out = collect(&debounce(&values.stream(), &ticks.stream(),
+                                    Duration::new(0, 100)));
+        values.send(1);
+        ticks.send(50);
+        values.send(2);
+        ticks.send(100);
+        assert!(out.sample().is_empty());
+        ticks.send(150);
+        ticks.send(300);
+        assert_eq!(out.sample(), vec![2]);
+    }
+
+    #[test]
+    fn samples_a_signal_every_interval() {
+        let ticks = Sink::new();
+        let out = collect(&sample_every(&Signal::new(7), &ticks.stream(),
+                                        Duration::new(0, 100)));
+        for &now in &[0, 40, 100, 150, 230] {
+            ticks.send(now);
+        }
+        assert_eq!(out.sample().len(), 3);
+    }
+
+    #[test]
+    fn releases_delayed_values_in_order() {
+        let queue = DelayQueue::new().push(10, 'a').push(20, 'b').push(15, 'c');
+        assert_eq!(queue.due(9), vec![]);
+        assert_eq!(queue.due(15), vec!['a']);
+        let queue = queue.advance(20);
+        assert!(queue.pending.is_empty());
+    }
+
+    #[test]
+    fn fires_once_after_the_deadline() {
+        let ticks = Sink::new();
+        let out = collect(&after(&ticks.stream(), 100));
+        for &now in &[0, 90, 120, 200] {
+            ticks.send(now);
+        }
+        assert_eq!(out.sample(), vec![120]);
+    }
+
+    #[test]
+    fn catches_up_with_missed_periods() {
+        let mut interval = Interval::new(5, 10);
+        assert_eq!(interval.advance(14), vec![]);
+        assert_eq!(interval.advance(15), vec![15]);
+        assert_eq!(interval.advance(48), vec![25, 35, 45]);
+        assert_eq!(interval.next, 55);
+    }
+}
@@ -0,0 +1,27 @@
+use carboxyl::{Signal, Stream};
+use region::{Rect, Region};
+
+/// Where the parts of a custom title bar are, in window coordinates.
+///
+/// The buttons are expected to lie within the bar.
+#[derive(Clone)]
+pub struct TitleBarLayout {
+    pub bar: Signal<Rect>,
+    pub close: Signal<Rect>,
+    pub minimize: Signal<Rect>,
+    pub maximize: Signal<Rect>
+}
+
+/// Pointer events of a custom title bar, for windows without decorations.
+///
+/// glutin can neither minimize nor maximize a window, so the buttons only
+/// report their clicks for the application to act on.
+#[derive(Clone)]
+pub struct TitleBar {
+    pub close: Region,
+    pub minimize: Region,
+    pub maximize: Region,
+    /// Fires on left presses on the bar outside of the buttons. The driver
+    /// starts dragging the window on these by itself.
+    pub drags: Stream<()>
+}
@@ -0,0 +1,161 @@
+use carboxyl::{Signal, Stream};
+use glutin::VirtualKeyCode;
+use ::{Event, Button, Modifiers};
+
+/// A reversible change to a document of type `D`.
+pub trait Command<D>: Clone + Send + Sync + 'static {
+    fn apply(&self, document: D) -> D;
+
+    /// Undo the effect of `apply` on the document it returned.
+    fn revert(&self, document: D) -> D;
+}
+
+/// A document with the commands that can be undone and redone.
+#[derive(Clone, Debug, PartialEq)]
+pub struct History<D, C> {
+    pub document: D,
+    /// Applied commands, most recent last.
+    pub undo: Vec<C>,
+    /// Undone commands, most recently undone last.
+    pub redo: Vec<C>
+}
+
+impl<D, C: Command<D>> History<D, C> {
+    pub fn new(document: D) -> History<D, C> {
+        History { document: document, undo: Vec::new(), redo: Vec::new() }
+    }
+}
+
+#[derive(Clone)]
+pub enum HistoryUpdate<C> {
+    Do(C),
+    Undo,
+    Redo
+}
+
+impl<C> HistoryUpdate<C> {
+    pub fn apply<D>(self, current: History<D, C>) -> History<D, C>
+        where C: Command<D>
+    {
+        let mut history = current;
+        match self {
+            // A new command forks the history, so the undone ones are lost
+            HistoryUpdate::Do(command) => {
+                history.document = command.apply(history.document);
+                history.undo.push(command);
+                history.redo.clear();
+            },
+            HistoryUpdate::Undo =>
+                if let Some(command) = history.undo.pop() {
+                    history.document = command.revert(history.document);
+                    history.redo.push(command);
+                },
+            HistoryUpdate::Redo =>
+                if let Some(command) = history.redo.pop() {
+                    history.document = command.apply(history.document);
+                    history.undo.push(command);
+                }
+        }
+        history
+    }
+}
+
+/// The history action of a shortcut: Ctrl+Z undoes, Ctrl+Shift+Z and
+/// Ctrl+Y redo.
+fn shortcut<C>(modifiers: &Modifiers, event: Event) -> Option<HistoryUpdate<C>> {
+    match event {
+        Event::Press(Button::Keyboard(VirtualKeyCode::Z)) if modifiers.ctrl() =>
+            Some(if modifiers.shift() {
+                HistoryUpdate::Redo
+            } else {
+                HistoryUpdate::Undo
+            }),
+        Event::Press(Button::Keyboard(VirtualKeyCode::Y)) if modifiers.ctrl() =>
+            Some(HistoryUpdate::Redo),
+        _ => None
+    }
+}
+
+/// A document maintained by an undo history.
+#[derive(Clone)]
+pub struct UndoModel<D> {
+    pub document: Signal<D>,
+    pub can_undo: Signal<bool>,
+    pub can_redo: Signal<bool>
+}
+
+/// Apply `commands` to a document, undoing and redoing them on the usual
+/// keyboard shortcuts in `events`.
+///
+/// As with `text_field`, `modifiers` should be tracked from all of the
+/// window's events, even if `events` only holds those of one widget.
+pub fn undo_history<D, C>(initial: D, commands: &Stream<C>, events: &Stream<Event>,
+                          modifiers: &Signal<Modifiers>)
+    -> UndoModel<D>
+    where D: Clone + Send + Sync + 'static,
+          C: Command<D>
+{
+    let shortcuts = modifiers
+        .snapshot(events, |modifiers, event| shortcut(&modifiers, event))
+        .filter_some();
+    let history = commands.map(HistoryUpdate::Do)
+        .merge(&shortcuts)
+        .fold(History::new(initial), |history, update| update.apply(history));
+    UndoModel {
+        document: history.map(|history| history.document),
+        can_undo: history.map(|history| !history.undo.is_empty()),
+        can_redo: history.map(|history| !history.redo.is_empty())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use carboxyl::Sink;
+    use glutin::VirtualKeyCode::{LControl, LShift, Z};
+    use ::{Button, Event, Modifiers};
+    use super::*;
+
+    #[derive(Clone)]
+    struct Add(i32);
+
+    impl Command<i32> for Add {
+        fn apply(&self, document: i32) -> i32 { document + self.0 }
+        fn revert(&self, document: i32) -> i32 { document - self.0 }
+    }
+
+    #[test]
+    fn new_commands_drop_the_redo_history() {
+        let history = vec![
+            HistoryUpdate::Do(Add(1)), HistoryUpdate::Do(Add(2)),
+            HistoryUpdate::Undo, HistoryUpdate::Do(Add(5)), HistoryUpdate::Redo
+        ];
+        let history = history.into_iter()
+            .fold(History::new(0), |history, update| update.apply(history));
+        assert_eq!(history.document, 6);
+        assert!(history.redo.is_empty());
+    }
+
+    #[test]
+    fn undoes_and_redoes_on_shortcuts() {
+        let commands = Sink::new();
+        let events = Sink::new();
+        let modifiers = events.stream()
+            .fold(Modifiers::default(), |modifiers, event| modifiers.update(&event));
+        let model = undo_history(0, &commands.stream(), &events.stream(), &modifiers);
+        let key = |key| Event::Press(Button::Keyboard(key));
+        commands.send(Add(3));
+        commands.send(Add(4));
+        events.send(key(LControl));
+        events.send(key(Z));
+        assert_eq!(model.document.sample(), 3);
+        events.send(key(Z));
+        events.send(key(Z));
+        assert_eq!(model.document.sample(), 0);
+        assert!(!model.can_undo.sample());
+        events.send(key(LShift));
+        events.send(key(Z));
+        assert_eq!(model.document.sample(), 3);
+        assert!(model.can_undo.sample() && model.can_redo.sample());
+    }
+}
@@ -1,4 +1,4 @@
-use ::{Context, WindowProperties, Cursor};
+use ::{Context, WindowProperties, Cursor, LockKeys};
 
 #[derive(Clone)]
 pub enum CursorUpdate {
@@ -41,10 +41,27 @@ impl WindowUpdate {
     }
 }
 
+#[derive(Clone)]
+pub enum LockKeyUpdate {
+    ToggleCaps,
+    ToggleNum
+}
+
+impl LockKeyUpdate {
+    pub fn apply(self, current: LockKeys) -> LockKeys {
+        use self::LockKeyUpdate::*;
+        match self {
+            ToggleCaps => LockKeys { caps: !current.caps, .. current },
+            ToggleNum => LockKeys { num: !current.num, .. current }
+        }
+    }
+}
+
 #[derive(Clone)]
 pub enum Update {
     Window(WindowUpdate),
-    Cursor(CursorUpdate)
+    Cursor(CursorUpdate),
+    LockKeys(LockKeyUpdate)
 }
 
 impl Update {
@@ -53,7 +70,9 @@ impl Update {
             Update::Cursor(update) =>
                 Context { cursor: update.apply(current.cursor), .. current },
             Update::Window(update) =>
-                Context { window: update.apply(current.window), .. current }
+                Context { window: update.apply(current.window), .. current },
+            Update::LockKeys(update) =>
+                Context { lock_keys: update.apply(current.lock_keys), .. current }
         }
     }
 }
@@ -62,7 +81,7 @@ impl Update {
 #[cfg(test)]
 mod tests {
     use super::Update;
-    use ::{WindowProperties, Context, Cursor};
+    use ::{WindowProperties, Context, Cursor, LockKeys};
 
     fn default<T: Default>() -> T { Default::default() }
 
@@ -125,4 +144,12 @@ mod tests {
                 .focus
         );
     }
+
+    #[test]
+    fn toggles_lock_keys_independently() {
+        use super::LockKeyUpdate::{ToggleCaps, ToggleNum};
+        let current = LockKeys { caps: false, num: true };
+        assert_eq!(ToggleCaps.apply(current), LockKeys { caps: true, num: true });
+        assert_eq!(ToggleNum.apply(current), LockKeys { caps: false, num: false });
+    }
 }